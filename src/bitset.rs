@@ -0,0 +1,99 @@
+const WORD_BITS: usize = u64::BITS as usize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// A fixed-width set of small integers such as tile indices.
+pub struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    /// Constructs an empty `BitSet` that can hold `0..len`.
+    pub fn new(len: usize) -> Self {
+        return Self {
+            words: vec![0; len.div_ceil(WORD_BITS)],
+        };
+    }
+
+    /// Constructs a `BitSet` containing all of `0..len`.
+    pub fn full(len: usize) -> Self {
+        let mut set = Self::new(len);
+        for word in set.words.iter_mut() {
+            *word = u64::MAX;
+        }
+        if !len.is_multiple_of(WORD_BITS) {
+            let last = set.words.len() - 1;
+            set.words[last] = (1 << (len % WORD_BITS)) - 1;
+        }
+        return set;
+    }
+
+    /// Adds `i`, returning true if it was not already present.
+    pub fn insert(&mut self, i: usize) -> bool {
+        let word = &mut self.words[i / WORD_BITS];
+        let bit = 1 << (i % WORD_BITS);
+        let inserted = *word & bit == 0;
+        *word |= bit;
+        return inserted;
+    }
+
+    /// Removes `i`, returning true if it was present.
+    pub fn remove(&mut self, i: usize) -> bool {
+        let word = &mut self.words[i / WORD_BITS];
+        let bit = 1 << (i % WORD_BITS);
+        let removed = *word & bit != 0;
+        *word &= !bit;
+        return removed;
+    }
+
+    /// Returns true if `i` is present.
+    pub fn contains(&self, i: usize) -> bool {
+        return self.words[i / WORD_BITS] & (1 << (i % WORD_BITS)) != 0;
+    }
+
+    /// Returns true if no value is present.
+    pub fn is_empty(&self) -> bool {
+        return self.words.iter().all(|word| *word == 0);
+    }
+
+    /// Returns the values in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        return self.words.iter().enumerate().flat_map(|(i, word)| {
+            let mut word = *word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(i * WORD_BITS + bit)
+            })
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full() {
+        let set = BitSet::full(70);
+        assert_eq!(Vec::from_iter(set.iter()), Vec::from_iter(0..70));
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut set = BitSet::new(130);
+        assert!(set.insert(3));
+        assert!(set.insert(129));
+        assert!(!set.insert(3));
+        assert_eq!(Vec::from_iter(set.iter()), vec![3, 129]);
+        assert!(set.remove(3));
+        assert!(!set.remove(3));
+        assert!(set.contains(129));
+        assert!(set.remove(129));
+        assert!(!set.contains(129));
+        assert_eq!(set.iter().next(), None);
+        assert!(set.is_empty());
+    }
+}
@@ -0,0 +1,59 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::error::Result;
+use super::vox::Vox;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// The number of temporary files written by this process.
+static TEMPORARY_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the 64-bit FNV-1a hash of `bytes`, which is stable across builds.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash = FNV_OFFSET_BASIS;
+    for byte in bytes.iter() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    return hash;
+}
+
+/// Returns the path of the tile `name` in `cache_dir`, writing it if it is not cached.
+///
+/// Files are named by the tile and the hash of their contents and written to
+/// a temporary file before being renamed into place, so concurrent runs can
+/// share a cache.
+pub fn vox_path(cache_dir: &Path, name: &str, vox: &Vox) -> Result<PathBuf> {
+    let bytes = vox.to_bytes()?;
+    let path = cache_dir.join(format!("{}.{:016x}.vox", name, fnv1a(&bytes)));
+    if !path.is_file() {
+        fs::create_dir_all(cache_dir)?;
+        let temporary_count = TEMPORARY_COUNT.fetch_add(1, Ordering::Relaxed);
+        let temporary_extension = format!("vox.{}-{}", process::id(), temporary_count);
+        let temporary_path = path.with_extension(temporary_extension);
+        fs::write(&temporary_path, &bytes)?;
+        fs::rename(&temporary_path, &path)?;
+    }
+    return Ok(path);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vox_path() {
+        let cache_dir = Path::new("tests/output/cache_test");
+        let vox = Vox::open("tests/samples/stairs/stairs-1-path.vox").unwrap();
+        let path = vox_path(cache_dir, "path@R90", &vox).unwrap();
+        assert!(path.file_name().unwrap().to_str().unwrap().starts_with("path@R90."));
+        assert_eq!(Vox::open(&path).unwrap(), vox);
+        assert_eq!(vox_path(cache_dir, "path@R90", &vox).unwrap(), path);
+        let other_vox = Vox::open("tests/samples/stairs/stairs-2-stairs_0.vox").unwrap();
+        assert_ne!(vox_path(cache_dir, "path@R90", &other_vox).unwrap(), path);
+    }
+}
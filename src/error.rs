@@ -0,0 +1,112 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use super::model::Face;
+use super::retry::Attempts;
+use super::wave::Contradiction;
+
+#[derive(Debug)]
+/// An error raised while loading a sample directory or running `wfc`.
+pub enum Error {
+    /// Reading or writing a file failed.
+    Io(io::Error),
+    /// A sample directory `config.json` could not be parsed.
+    Json(serde_json::Error),
+    /// A sample directory `config.json` has an invalid value.
+    InvalidConfig(String),
+    /// A VOX file did not match the expected chunk layout at byte `offset`.
+    MalformedVox { offset: u64, message: String },
+    /// A tile in `config.json` has no matching VOX file.
+    MissingTile(PathBuf),
+    /// A tile name is empty or contains `@` or a path separator.
+    InvalidTileName(String),
+    /// A pin is outside the grid or selects no tile.
+    InvalidPin(String),
+    /// Pinned tiles leave the cell at `x`, `y`, `z`, or a neighbor of it,
+    /// without tiles before any observation.
+    ConflictingPins { x: usize, y: usize, z: usize },
+    /// The boundary outside `face` leaves a cell without tiles before any
+    /// observation.
+    UnsatisfiableBoundary(Face),
+    /// The tiles cannot satisfy the constraints of the wave graph.
+    Unsatisfiable(Contradiction),
+    /// A grid without cells cannot be rendered to a VOX file.
+    EmptyGrid,
+    /// Every attempt allowed by a `RetryPolicy` ended in a contradiction.
+    GaveUp(Attempts),
+}
+
+/// A `Result` with the crate `Error` type.
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            Error::Io(e) => write!(f, "i/o error: {}", e),
+            Error::Json(e) => write!(f, "invalid config: {}", e),
+            Error::InvalidConfig(message) => write!(f, "invalid config: {}", message),
+            Error::MalformedVox { offset, message } => {
+                write!(f, "malformed vox at byte {}: {}", offset, message)
+            }
+            Error::MissingTile(path) => write!(f, "missing tile file {:?}", path),
+            Error::InvalidTileName(name) => write!(f, "invalid tile name {:?}", name),
+            Error::InvalidPin(message) => write!(f, "invalid pin: {}", message),
+            Error::ConflictingPins { x, y, z } => {
+                write!(f, "pinned tiles conflict at x={} y={} z={}", x, y, z)
+            }
+            Error::UnsatisfiableBoundary(face) => {
+                write!(f, "no tiles satisfy the boundary outside face {:?}", face)
+            }
+            Error::Unsatisfiable(c) if !c.names.is_empty() => write!(
+                f,
+                "unsatisfiable constraints at wave {} on face {:?} given tiles {:?}",
+                c.wave, c.face, c.names
+            ),
+            Error::Unsatisfiable(c) => write!(
+                f,
+                "unsatisfiable constraints at wave {} on face {:?} given tiles {:?}",
+                c.wave, c.face, c.tiles
+            ),
+            Error::EmptyGrid => write!(f, "cannot render an empty grid to a vox file"),
+            Error::GaveUp(attempts) => {
+                write!(f, "gave up after {} attempts", attempts.len())?;
+                if let Some((wave, count)) = attempts.wave_counts().first() {
+                    write!(f, ", most contradictions at wave {} ({} times)", wave, count)?;
+                }
+                if let Some((face, count)) = attempts.face_counts().first() {
+                    write!(f, " and on face {:?} ({} times)", face, count)?;
+                }
+                Ok(())
+            }
+        };
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        return match self {
+            Error::Io(e) => Some(e),
+            Error::Json(e) => Some(e),
+            _ => None,
+        };
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        return Error::Io(e);
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        return Error::Json(e);
+    }
+}
+
+impl From<Contradiction> for Error {
+    fn from(c: Contradiction) -> Self {
+        return Error::Unsatisfiable(c);
+    }
+}
@@ -0,0 +1,73 @@
+use super::tile::{Orientation, Rotation};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A collapsed tile at a single grid coordinate.
+pub struct Cell {
+    pub tile: usize,
+    pub name: String,
+    /// The rotation of the tile's up face about the z axis.
+    pub rotation: Rotation,
+    /// The full rotation of the tile, which may tip it onto another face.
+    pub orientation: Orientation,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The collapsed result of `wfc`, with cells stored in x, y, z order.
+pub struct Grid {
+    width: usize,
+    depth: usize,
+    height: usize,
+    seed: u64,
+    cells: Vec<Cell>,
+}
+
+impl Grid {
+    /// Constructs a `Grid` from `cells` ordered by x, then y, then z.
+    pub fn new(width: usize, depth: usize, height: usize, seed: u64, cells: Vec<Cell>) -> Self {
+        assert_eq!(cells.len(), width * depth * height);
+        return Self {
+            width: width,
+            depth: depth,
+            height: height,
+            seed: seed,
+            cells: cells,
+        };
+    }
+
+    pub fn width(&self) -> usize {
+        return self.width;
+    }
+
+    pub fn depth(&self) -> usize {
+        return self.depth;
+    }
+
+    pub fn height(&self) -> usize {
+        return self.height;
+    }
+
+    /// Returns the seed that produced this grid.
+    pub fn seed(&self) -> u64 {
+        return self.seed;
+    }
+
+    /// Returns the cell at the given coordinate.
+    pub fn get(&self, x: usize, y: usize, z: usize) -> &Cell {
+        return &self.cells[x + (y * self.width) + (z * self.width * self.depth)];
+    }
+
+    /// Returns all cells ordered by x, then y, then z.
+    pub fn cells(&self) -> &[Cell] {
+        return &self.cells;
+    }
+
+    /// Returns each cell paired with its coordinate.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize, usize), &Cell)> {
+        let width = self.width;
+        let depth = self.depth;
+        return self.cells.iter().enumerate().map(move |(i, cell)| {
+            let coordinate = (i % width, (i / width) % depth, i / (width * depth));
+            (coordinate, cell)
+        });
+    }
+}
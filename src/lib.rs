@@ -1,29 +1,61 @@
+#![allow(clippy::needless_return, clippy::redundant_field_names)]
+
+mod bitset;
+mod cache;
+mod error;
+mod grid;
 mod model;
+mod observer;
+mod palette;
+mod retry;
 mod tile;
+mod validation;
 mod vox;
 mod wave;
 
-use model::Model;
+pub use error::{Error, Result};
+pub use grid::{Cell, Grid};
+pub use model::{Boundary, Face, Model, OutputFormat, Pin};
+#[cfg(feature = "log")]
+pub use observer::LogObserver;
+pub use observer::Observer;
+pub use palette::Approximation;
+pub use retry::{Attempts, RetryPolicy, SeedDerivation};
+pub use tile::{Axis, Orientation, Rotation};
+pub use validation::{AsymmetricAdjacency, DeadFace, OneSidedConnector, TileName, ValidationReport};
+pub use vox::Vox;
+pub use wave::{Contradiction, Heuristic};
 
-pub fn run(sample_dir: &str, width: usize, depth: usize, height: usize, output_file: &str) {
+/// Runs `wfc`, retrying contradictions according to `policy`, and returns
+/// the seed of the successful attempt.
+///
+/// With `SeedDerivation::Chained` the first attempt uses `seed` itself and
+/// every retry derives its seed from the previous attempt, so the seed
+/// recorded in `output_file` reproduces the same output when passed back in.
+pub fn run(
+    sample_dir: &str,
+    width: usize,
+    depth: usize,
+    height: usize,
+    output_file: &str,
+    seed: u64,
+    policy: &RetryPolicy,
+) -> Result<u64> {
     let model = Model::new(sample_dir, width, depth, height, output_file);
-    loop {
-        let wfc = model.wfc();
-        match wfc {
-            Ok(()) => {
-                println!("Wave function collapse completed successfully, exiting");
-                break;
-            }
-            Err(e) => {
-                println!("Wave function collapse failed due to {:?}, retrying", e);
-            }
-        }
-    }
+    let grid = model.wfc(seed, policy)?;
+    return Ok(grid.seed());
 }
 
-pub fn debug(sample_dir: &str, width: usize, depth: usize, height: usize, output_file: &str) {
+pub fn debug(
+    sample_dir: &str,
+    width: usize,
+    depth: usize,
+    height: usize,
+    output_file: &str,
+    seed: u64,
+) -> Result<()> {
     let model = Model::new(sample_dir, width, depth, height, output_file);
-    let wfc = model.debug();
+    return model.debug(seed);
 }
 
 // Return a vector of vectors of tile voxels vector is a flat array with implicit xyzs
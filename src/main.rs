@@ -0,0 +1,284 @@
+#![allow(clippy::needless_return, clippy::redundant_field_names)]
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use clap::builder::RangedU64ValueParser;
+use clap::{Parser, Subcommand, ValueEnum};
+
+use wfc::{
+    Axis, Boundary, Error, Face, Heuristic, Model, OutputFormat, RetryPolicy, Rotation,
+    SeedDerivation, Vox,
+};
+
+/// Exit code for a failure to read or write a file.
+const EXIT_IO: u8 = 1;
+/// Exit code for an invalid sample directory or VOX file.
+const EXIT_INVALID: u8 = 3;
+/// Exit code for tiles that cannot satisfy the constraints of the grid.
+const EXIT_UNSATISFIABLE: u8 = 4;
+/// Exit code for running out of attempts after contradictions.
+const EXIT_GAVE_UP: u8 = 5;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "wfc",
+    about = "Generate voxel maps with the Wave Function Collapse Algorithm",
+    after_help = "Exit codes: 0 success, 1 i/o error, 2 invalid arguments, \
+                  3 invalid sample directory or VOX file, 4 unsatisfiable constraints, \
+                  5 gave up after contradictions"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Collapse a grid of tiles and render it to an output file.
+    Generate {
+        /// The sample directory holding `config.json` and the tiles.
+        sample_dir: String,
+        /// The mv_import or VOX file to write.
+        output_file: String,
+        #[arg(long, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+        width: usize,
+        #[arg(long, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+        depth: usize,
+        #[arg(long, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+        height: usize,
+        /// The seed of the first attempt, which defaults to the current time.
+        #[arg(long)]
+        seed: Option<u64>,
+        #[arg(long, value_enum, default_value_t = Format::MvImport)]
+        format: Format,
+        /// The maximum number of attempts, retrying indefinitely if not given.
+        #[arg(long)]
+        max_attempts: Option<usize>,
+        /// The number of seconds after which no new attempt is started.
+        #[arg(long, value_parser = parse_time_budget)]
+        time_budget: Option<Duration>,
+        /// Derive retry seeds by counting up from the first seed instead of
+        /// from the previous attempt.
+        #[arg(long)]
+        sequential_seeds: bool,
+        /// The number of recent observations to backtrack over on a contradiction.
+        #[arg(long, default_value_t = 0)]
+        backtrack_depth: usize,
+        #[arg(long, value_enum, default_value_t = HeuristicArg::Shannon)]
+        heuristic: HeuristicArg,
+        /// The directory generated tiles are written to for mv_import output.
+        #[arg(long)]
+        cache_dir: Option<String>,
+        /// The connector id outside a face of the grid, as `FACE=ID` such as
+        /// `down=99`, which cells on that face must match, or `FACE=periodic`
+        /// to wrap the grid around the axis of the face.
+        #[arg(long = "boundary", value_name = "FACE=ID", value_parser = parse_boundary)]
+        boundaries: Vec<(Face, Boundary)>,
+    },
+    /// Load a sample directory and report problems with its tiles without collapsing.
+    Validate {
+        sample_dir: String,
+        /// Exit with the invalid sample directory code if any problem is found.
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Print the model sizes and colors of a VOX file.
+    Inspect { vox_file: PathBuf },
+    /// Rotate and reflect a VOX file.
+    Transform {
+        input_file: PathBuf,
+        output_file: PathBuf,
+        /// The rotation about the z axis, applied before any reflection.
+        #[arg(long, value_enum, default_value_t = RotationArg::R0)]
+        rotate: RotationArg,
+        #[arg(long, value_enum)]
+        reflect: Option<AxisArg>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Format {
+    MvImport,
+    Vox,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum HeuristicArg {
+    MinCount,
+    Shannon,
+    Scanline,
+    Random,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum RotationArg {
+    R0,
+    R90,
+    R180,
+    R270,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum AxisArg {
+    X,
+    Y,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    return match run(cli.command) {
+        Ok(exit_code) => exit_code,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::from(exit_code(&e))
+        }
+    };
+}
+
+/// Returns the exit code reporting `error`.
+fn exit_code(error: &Error) -> u8 {
+    return match error {
+        Error::Io(_) => EXIT_IO,
+        Error::Json(_)
+        | Error::InvalidConfig(_)
+        | Error::MalformedVox { .. }
+        | Error::MissingTile(_)
+        | Error::InvalidTileName(_)
+        | Error::InvalidPin(_)
+        | Error::EmptyGrid => EXIT_INVALID,
+        Error::ConflictingPins { .. } | Error::UnsatisfiableBoundary(_) => EXIT_UNSATISFIABLE,
+        Error::Unsatisfiable(_) => EXIT_UNSATISFIABLE,
+        Error::GaveUp(_) => EXIT_GAVE_UP,
+    };
+}
+
+fn run(command: Command) -> wfc::Result<ExitCode> {
+    match command {
+        Command::Generate {
+            sample_dir,
+            output_file,
+            width,
+            depth,
+            height,
+            seed,
+            format,
+            max_attempts,
+            time_budget,
+            sequential_seeds,
+            backtrack_depth,
+            heuristic,
+            cache_dir,
+            boundaries,
+        } => {
+            let mut model = Model::new(&sample_dir, width, depth, height, &output_file);
+            model.set_output_format(match format {
+                Format::MvImport => OutputFormat::MvImport,
+                Format::Vox => OutputFormat::Vox,
+            });
+            model.set_backtrack_depth(backtrack_depth);
+            model.set_heuristic(match heuristic {
+                HeuristicArg::MinCount => Heuristic::MinCount,
+                HeuristicArg::Shannon => Heuristic::Shannon,
+                HeuristicArg::Scanline => Heuristic::Scanline,
+                HeuristicArg::Random => Heuristic::Random,
+            });
+            if let Some(cache_dir) = cache_dir {
+                model.set_cache_dir(&cache_dir);
+            }
+            for (face, boundary) in boundaries {
+                model.set_boundary(face, boundary);
+            }
+            let seed_derivation = match sequential_seeds {
+                true => SeedDerivation::Sequential,
+                false => SeedDerivation::Chained,
+            };
+            let policy = RetryPolicy::new(max_attempts, time_budget, seed_derivation);
+            let seed = seed.unwrap_or_else(time_seed);
+            let grid = model.wfc(seed, &policy)?;
+            println!("seed {}", grid.seed());
+        }
+        Command::Validate { sample_dir, strict } => {
+            let model = Model::new(&sample_dir, 1, 1, 1, "");
+            println!("{} tiles", model.tile_names()?.len());
+            let report = model.validate()?;
+            print!("{}", report);
+            if strict && !report.is_empty() {
+                return Ok(ExitCode::from(EXIT_INVALID));
+            }
+        }
+        Command::Inspect { vox_file } => {
+            let vox = Vox::open(&vox_file)?;
+            let sizes = vox.sizes();
+            println!("{} models", sizes.len());
+            for (i, size) in sizes.iter().enumerate() {
+                println!("model {}: {}x{}x{}", i, size[0], size[1], size[2]);
+            }
+            let color_counts = vox.color_counts();
+            let color_count = color_counts.iter().filter(|count| **count > 0).count();
+            let voxel_count: usize = color_counts.iter().sum();
+            println!("{} voxels in {} colors", voxel_count, color_count);
+        }
+        Command::Transform {
+            input_file,
+            output_file,
+            rotate,
+            reflect,
+        } => {
+            let vox = Vox::open(&input_file)?;
+            let vox = vox.rotated(&match rotate {
+                RotationArg::R0 => Rotation::R0,
+                RotationArg::R90 => Rotation::R90,
+                RotationArg::R180 => Rotation::R180,
+                RotationArg::R270 => Rotation::R270,
+            });
+            let vox = match reflect {
+                Some(AxisArg::X) => vox.reflected(&Axis::X),
+                Some(AxisArg::Y) => vox.reflected(&Axis::Y),
+                None => vox,
+            };
+            vox.write(&output_file)?;
+        }
+    }
+    return Ok(ExitCode::SUCCESS);
+}
+
+/// Parses a time budget in seconds.
+fn parse_time_budget(argument: &str) -> Result<Duration, String> {
+    let seconds: f64 = argument
+        .parse()
+        .map_err(|_| format!("invalid number of seconds {:?}", argument))?;
+    return Duration::try_from_secs_f64(seconds).map_err(|e| e.to_string());
+}
+
+/// Parses a `FACE=ID` or `FACE=periodic` boundary argument.
+fn parse_boundary(argument: &str) -> Result<(Face, Boundary), String> {
+    let (face, id) = argument
+        .split_once('=')
+        .ok_or_else(|| format!("expected FACE=ID, got {:?}", argument))?;
+    let face = match face {
+        "left" => Face::Left,
+        "right" => Face::Right,
+        "front" => Face::Front,
+        "back" => Face::Back,
+        "down" => Face::Down,
+        "up" => Face::Up,
+        _ => return Err(format!("unknown face {:?}", face)),
+    };
+    if id == "periodic" {
+        return Ok((face, Boundary::Periodic));
+    }
+    let id = id
+        .parse()
+        .map_err(|_| format!("invalid connector id {:?}", id))?;
+    return Ok((face, Boundary::Connector(id)));
+}
+
+/// Returns a seed from the current time.
+fn time_seed() -> u64 {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    return elapsed.as_nanos() as u64;
+}
@@ -1,13 +1,23 @@
-use std::collections::HashSet;
+use std::collections::HashMap;
+use std::fs;
 use std::fs::File;
 use std::io::{BufWriter, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 use serde::{Deserialize, Serialize};
 
+use super::bitset::BitSet;
+use super::grid::{Cell, Grid};
 use super::tile::Tiles;
-use super::wave::Waves;
-use super::wave::Contradiction;
+use super::cache;
+use super::error::{Error, Result};
+use super::observer::Observer;
+use super::palette::MergedPalette;
+use super::retry::{Attempts, RetryPolicy};
+use super::validation::ValidationReport;
+use super::vox::Vox;
+use super::wave::{Heuristic, Waves};
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
 /// A face of a 3D tile.
@@ -20,6 +30,100 @@ pub enum Face {
     Up,
 }
 
+impl Face {
+    /// Every face, ordered by `Face::index`.
+    pub const ALL: [Face; 6] = [
+        Face::Left,
+        Face::Right,
+        Face::Front,
+        Face::Back,
+        Face::Down,
+        Face::Up,
+    ];
+
+    /// Returns the position of the face in `Face::ALL`.
+    pub fn index(&self) -> usize {
+        return match self {
+            Face::Left => 0,
+            Face::Right => 1,
+            Face::Front => 2,
+            Face::Back => 3,
+            Face::Down => 4,
+            Face::Up => 5,
+        };
+    }
+
+    /// Returns the unit vector pointing out of the face.
+    pub fn normal(&self) -> [i32; 3] {
+        return match self {
+            Face::Left => [-1, 0, 0],
+            Face::Right => [1, 0, 0],
+            Face::Front => [0, -1, 0],
+            Face::Back => [0, 1, 0],
+            Face::Down => [0, 0, -1],
+            Face::Up => [0, 0, 1],
+        };
+    }
+
+    /// Returns the face whose normal is `normal`, if any.
+    pub fn towards(normal: [i32; 3]) -> Option<Face> {
+        return Face::ALL.into_iter().find(|face| face.normal() == normal);
+    }
+
+    /// Returns the face on the opposite side of a tile.
+    pub fn opposite(&self) -> Face {
+        return match self {
+            Face::Left => Face::Right,
+            Face::Right => Face::Left,
+            Face::Front => Face::Back,
+            Face::Back => Face::Front,
+            Face::Down => Face::Up,
+            Face::Up => Face::Down,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The format of the output file.
+pub enum OutputFormat {
+    /// A MagicaVoxel Viewer mv_import file referencing the tile files.
+    MvImport,
+    /// A self-contained VOX file holding the voxels of every tile.
+    Vox,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A restriction on the tiles of a cell, applied before the first observation.
+///
+/// Selectors match tiles by name, such as `road@R90`, by the name of the
+/// config tile they were generated from, or by one of their `tags`.
+pub enum Pin {
+    /// Only the tile with exactly this name.
+    Tile(String),
+    /// Only tiles matching any of the selectors.
+    Allow(Vec<String>),
+    /// Any tile matching none of the selectors.
+    Forbid(Vec<String>),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// The rule for what lies outside a face of the grid.
+pub enum Boundary {
+    /// Anything, leaving cells on the face unconstrained.
+    #[default]
+    Open,
+    /// A tile face with a `Symmetrical` connector of this id, which cells on
+    /// the face must fit like any neighbor, so only their `Symmetrical`
+    /// connectors with the id fit.
+    Connector(i64),
+    /// The cells on the opposite face, so the grid tiles seamlessly along
+    /// the axis of the face.
+    Periodic,
+}
+
+/// An x, y, z position in the wave graph.
+type Coordinate = (usize, usize, usize);
+
 /// A container for the necesary data to run `wfc`.
 pub struct Model {
     sample_dir: String,
@@ -27,6 +131,13 @@ pub struct Model {
     depth: usize,
     height: usize,
     output_file: String,
+    cache_dir: Option<String>,
+    backtrack_depth: usize,
+    heuristic: Heuristic,
+    output_format: OutputFormat,
+    observer: Box<dyn Observer>,
+    pins: Vec<(Coordinate, Pin)>,
+    boundaries: [Boundary; 6],
 }
 
 impl Model {
@@ -44,159 +155,472 @@ impl Model {
             depth: depth,
             height: height,
             output_file: output_file.to_string(),
+            cache_dir: None,
+            backtrack_depth: 0,
+            heuristic: Heuristic::Shannon,
+            output_format: OutputFormat::MvImport,
+            observer: Box::new(()),
+            pins: Vec::new(),
+            boundaries: [Boundary::Open; 6],
         };
         return model;
     }
 
-    /// Runs the Wave Function Collapse Algorithm.
-    pub fn debug(&self) -> Result<(), ()> {
-        let mut coordinates = Vec::new();
-        let mut wave_graph = Vec::new();
-        for z in 0..self.height {
-            for y in 0..self.depth {
-                for x in 0..self.width {
-                    coordinates.push((x, y, z));
-                    wave_graph.push(self.wave_edges(x, y, z));
-                }
+    /// Backtracks over at most `depth` of the most recent observations on a
+    /// contradiction instead of failing the attempt. A `depth` of 0 disables
+    /// backtracking.
+    pub fn set_backtrack_depth(&mut self, depth: usize) {
+        self.backtrack_depth = depth;
+    }
+
+    /// Sets the `heuristic` used to choose the next wave to observe.
+    pub fn set_heuristic(&mut self, heuristic: Heuristic) {
+        self.heuristic = heuristic;
+    }
+
+    /// Sets the `output_format` the output file is rendered in.
+    pub fn set_output_format(&mut self, output_format: OutputFormat) {
+        self.output_format = output_format;
+    }
+
+    /// Sets the directory tiles without a source file are written to for
+    /// mv_import output, which defaults to `tiles` beside the output file.
+    pub fn set_cache_dir(&mut self, cache_dir: &str) {
+        self.cache_dir = Some(cache_dir.to_string());
+    }
+
+    /// Sets the `observer` notified of progress while collapsing waves.
+    pub fn set_observer(&mut self, observer: Box<dyn Observer>) {
+        self.observer = observer;
+    }
+
+    /// Restricts the tiles of the cell at `x`, `y`, `z` by `pin` in every
+    /// attempt. Pins on the same cell all apply.
+    pub fn pin(&mut self, x: usize, y: usize, z: usize, pin: Pin) {
+        self.pins.push(((x, y, z), pin));
+    }
+
+    /// Sets the `boundary` outside `face` of the grid, which is open by default.
+    ///
+    /// A periodic boundary wraps a whole axis, so making `face` periodic, or
+    /// no longer periodic, does the same to the opposite face.
+    pub fn set_boundary(&mut self, face: Face, boundary: Boundary) {
+        let opposite = face.opposite().index();
+        if boundary == Boundary::Periodic {
+            self.boundaries[opposite] = Boundary::Periodic;
+        } else if self.boundaries[opposite] == Boundary::Periodic {
+            self.boundaries[opposite] = Boundary::Open;
+        }
+        self.boundaries[face.index()] = boundary;
+    }
+
+    /// Runs the Wave Function Collapse Algorithm once, reporting any
+    /// contradiction to the observer, and renders the result to the output file.
+    pub fn debug(&self, seed: u64) -> Result<()> {
+        let tiles = self.tiles()?;
+        let grid = self.solve_tiles(seed, &tiles)?;
+        return self.output(&grid, &tiles);
+    }
+
+    /// Runs the Wave Function Collapse Algorithm, retrying according to
+    /// `policy`, and renders the result to the output file.
+    pub fn wfc(&self, seed: u64, policy: &RetryPolicy) -> Result<Grid> {
+        let tiles = self.tiles()?;
+        let grid = self.retry_tiles(seed, policy, &tiles)?;
+        self.output(&grid, &tiles)?;
+        return Ok(grid);
+    }
+
+    /// Runs the Wave Function Collapse Algorithm with random choices driven by `seed`.
+    pub fn solve(&self, seed: u64) -> Result<Grid> {
+        let tiles = self.tiles()?;
+        return self.solve_tiles(seed, &tiles);
+    }
+
+    /// Runs the Wave Function Collapse Algorithm, retrying according to `policy`.
+    pub fn solve_with_retries(&self, seed: u64, policy: &RetryPolicy) -> Result<Grid> {
+        let tiles = self.tiles()?;
+        return self.retry_tiles(seed, policy, &tiles);
+    }
+
+    /// Collapses a wave graph of `tiles`, retrying contradictions according to `policy`.
+    ///
+    /// A contradiction before any observation is returned straight away,
+    /// since no seed can avoid it.
+    fn retry_tiles(&self, seed: u64, policy: &RetryPolicy, tiles: &Tiles) -> Result<Grid> {
+        let start = Instant::now();
+        let wave_graph = self.wave_graph();
+        let constraints = tiles.constraints();
+        let mut attempts = Attempts::default();
+        let mut attempt_seed = seed;
+        loop {
+            // Contradictions before any observation do not depend on the seed
+            let waves = self.initial_waves(&wave_graph, &constraints, attempt_seed, tiles)?;
+            match self.collapse(attempt_seed, waves, tiles) {
+                Ok(grid) => return Ok(grid),
+                Err(Error::Unsatisfiable(c)) => attempts.push(attempt_seed, c),
+                Err(e) => return Err(e),
             }
+            let out_of_attempts = policy
+                .max_attempts
+                .is_some_and(|max_attempts| attempts.len() >= max_attempts);
+            let out_of_time = policy
+                .time_budget
+                .is_some_and(|time_budget| start.elapsed() >= time_budget);
+            if out_of_attempts || out_of_time {
+                return Err(Error::GaveUp(attempts));
+            }
+            attempt_seed = policy.seed_derivation.seed(seed, attempt_seed, attempts.len());
         }
-        let mut tiles = Tiles::from(&self.sample_dir).unwrap();
+    }
+
+    /// Returns the name of every tile, including transformed tiles, failing
+    /// if the sample directory cannot be loaded.
+    pub fn tile_names(&self) -> Result<Vec<String>> {
+        return Ok(self.tiles()?.names().clone());
+    }
+
+    /// Returns the problems with the tiles of the sample directory, including
+    /// transformed tiles, that can make `wfc` fail.
+    pub fn validate(&self) -> Result<ValidationReport> {
+        return Ok(self.tiles()?.validate());
+    }
+
+    /// Returns the tiles of the sample directory, including transformed tiles.
+    fn tiles(&self) -> Result<Tiles> {
+        let mut tiles = Tiles::from(&self.sample_dir)?;
         tiles.generate_transformed_tiles();
+        return Ok(tiles);
+    }
+
+    /// Collapses a wave graph of `tiles` into a `Grid`.
+    fn solve_tiles(&self, seed: u64, tiles: &Tiles) -> Result<Grid> {
+        let wave_graph = self.wave_graph();
         let constraints = tiles.constraints();
-        let mut waves = Waves::new(&wave_graph, &constraints);
+        let waves = self.initial_waves(&wave_graph, &constraints, seed, tiles)?;
+        return self.collapse(seed, waves, tiles);
+    }
+
+    /// Returns the waves of `wave_graph` restricted by the boundaries and pins,
+    /// before any observation.
+    fn initial_waves<'a>(
+        &self,
+        wave_graph: &'a Vec<Vec<(usize, Face)>>,
+        constraints: &HashMap<Face, Vec<BitSet>>,
+        seed: u64,
+        tiles: &'a Tiles,
+    ) -> Result<Waves<'a>> {
+        let mut waves = Waves::new(wave_graph, constraints, tiles.weights(), seed)
+            .map_err(|c| c.named(tiles.names()))
+            .inspect_err(|c| self.observer.contradiction(c))?;
+        waves.set_backtrack_depth(self.backtrack_depth);
+        waves.set_heuristic(self.heuristic);
+        self.apply_boundaries(&mut waves, tiles)?;
+        self.apply_pins(&mut waves, tiles)?;
+        return Ok(waves);
+    }
 
+    /// Observes and propogates `waves` until every wave is collapsed.
+    fn collapse(&self, seed: u64, mut waves: Waves, tiles: &Tiles) -> Result<Grid> {
+        let mut progress = 0;
         while !waves.are_collapsed() {
-            //println!("\n\nIteration {}", i);
-            //println!("Waves {:?}", waves);
-            let wave = waves.min_entropy_wave();
-            //println!("Min entropy wave {:?}", wave);
-            waves.observe(wave);
-            //println!("Observe {:?}", waves);
-            match waves.propogate(wave) {
-                Ok(_) => (),
+            let wave = waves.next_wave();
+            let tile = waves
+                .observe(wave)
+                .ok_or_else(|| Error::InvalidConfig(format!("wave {} has no tiles to observe", wave)))?;
+            self.observer.observe(wave, tile);
+            match waves.propogate() {
+                Ok(()) => self.observer.propagate(wave),
                 Err(c) => {
-                    let vox_paths = tiles.vox_paths();
-                    println!("Contradiction!");
-                    let coords = coordinates[c.wave];
-                    println!("Cannot propogate contraints from x={} y={} z={}", coords.0, coords.1, coords.2);
-                    println!("Given tiles:");
-                    for tile in c.tiles {
-                        println!("{:?}", vox_paths[tile]);
-                    }
-                    println!("On face {:?}", c.face);
-                    return Err(());
-                },
+                    let c = c.named(tiles.names());
+                    self.observer.contradiction(&c);
+                    waves.backtrack(c).map_err(|c| c.named(tiles.names()))?;
+                }
+            }
+            if waves.progress() > progress {
+                progress = waves.progress();
+                self.observer.progress(progress);
+            }
+        }
+        return Ok(self.grid(seed, tiles, &waves));
+    }
+
+    /// Restricts the waves on each face of the grid to tiles matching the
+    /// boundary outside it and propogates the restrictions.
+    fn apply_boundaries(&self, waves: &mut Waves, tiles: &Tiles) -> Result<()> {
+        for face in Face::ALL {
+            let id = match self.boundaries[face.index()] {
+                Boundary::Open | Boundary::Periodic => continue,
+                Boundary::Connector(id) => id,
             };
+            let allowed = tiles.with_connector(&face, id);
+            for wave in 0..self.width * self.depth * self.height {
+                if self.is_on_face(self.coordinate(wave), &face) && !waves.restrict(wave, &allowed) {
+                    return Err(Error::UnsatisfiableBoundary(face));
+                }
+            }
+            waves.propogate().map_err(|_| Error::UnsatisfiableBoundary(face.clone()))?;
         }
-        //println!("\n\nFinal {:?}", waves);
-        self.render(tiles.size(), tiles.vox_paths(), coordinates, waves.tiles());
         return Ok(());
     }
 
-    /// Runs the Wave Function Collapse Algorithm.
-    pub fn wfc(&self) -> Result<(), Contradiction> {
-        let mut coordinates = Vec::new();
+    /// Returns true if the cell at `coordinate` lies on `face` of the grid.
+    fn is_on_face(&self, coordinate: Coordinate, face: &Face) -> bool {
+        let (x, y, z) = coordinate;
+        return match face {
+            Face::Left => x == 0,
+            Face::Right => x == self.width - 1,
+            Face::Front => y == 0,
+            Face::Back => y == self.depth - 1,
+            Face::Down => z == 0,
+            Face::Up => z == self.height - 1,
+        };
+    }
+
+    /// Restricts `waves` by every pin and propogates the restrictions.
+    fn apply_pins(&self, waves: &mut Waves, tiles: &Tiles) -> Result<()> {
+        for ((x, y, z), pin) in self.pins.iter() {
+            if *x >= self.width || *y >= self.depth || *z >= self.height {
+                let cell = (x, y, z);
+                return Err(Error::InvalidPin(format!("cell {:?} is outside the grid", cell)));
+            }
+            let allowed = self.pinned_tiles(pin, tiles)?;
+            if !waves.restrict(self.edge_wave(*x, *y, *z), &allowed) {
+                return Err(Error::ConflictingPins { x: *x, y: *y, z: *z });
+            }
+        }
+        return waves.propogate().map_err(|c| {
+            let (x, y, z) = self.coordinate(c.wave);
+            Error::ConflictingPins { x: x, y: y, z: z }
+        });
+    }
+
+    /// Returns the tiles of `tiles` allowed by `pin`.
+    fn pinned_tiles(&self, pin: &Pin, tiles: &Tiles) -> Result<BitSet> {
+        let tile_count = tiles.names().len();
+        let select = |selector: &String| {
+            let selected = tiles.select(selector);
+            if selected.is_empty() {
+                return Err(Error::InvalidPin(format!("{:?} selects no tile", selector)));
+            }
+            return Ok(selected);
+        };
+        let allowed = match pin {
+            Pin::Tile(name) => {
+                let tile = tiles.names().iter().position(|tile_name| tile_name == name);
+                let tile = tile.ok_or_else(|| Error::InvalidPin(format!("no tile named {:?}", name)))?;
+                let mut allowed = BitSet::new(tile_count);
+                allowed.insert(tile);
+                allowed
+            }
+            Pin::Allow(selectors) => {
+                let mut allowed = BitSet::new(tile_count);
+                for selector in selectors.iter() {
+                    for tile in select(selector)?.iter() {
+                        allowed.insert(tile);
+                    }
+                }
+                allowed
+            }
+            Pin::Forbid(selectors) => {
+                let mut allowed = BitSet::full(tile_count);
+                for selector in selectors.iter() {
+                    for tile in select(selector)?.iter() {
+                        allowed.remove(tile);
+                    }
+                }
+                allowed
+            }
+        };
+        return Ok(allowed);
+    }
+
+    /// Returns the edges of every wave in the wave graph.
+    fn wave_graph(&self) -> Vec<Vec<(usize, Face)>> {
         let mut wave_graph = Vec::new();
         for z in 0..self.height {
             for y in 0..self.depth {
                 for x in 0..self.width {
-                    coordinates.push((x, y, z));
                     wave_graph.push(self.wave_edges(x, y, z));
                 }
             }
         }
-        let mut tiles = Tiles::from(&self.sample_dir).unwrap();
-        tiles.generate_transformed_tiles();
-        let constraints = tiles.constraints();
-        let mut waves = Waves::new(&wave_graph, &constraints);
+        return wave_graph;
+    }
 
-        while !waves.are_collapsed() {
-            //println!("\n\nIteration {}", i);
-            //println!("Waves {:?}", waves);
-            let wave = waves.min_entropy_wave();
-            //println!("Min entropy wave {:?}", wave);
-            waves.observe(wave);
-            //println!("Observe {:?}", waves);
-            waves.propogate(wave)?;
-            //println!("Propogate {:?}", waves);
-        }
-        //println!("\n\nFinal {:?}", waves);
-        self.render(tiles.size(), tiles.vox_paths(), coordinates, waves.tiles());
-        return Ok(());
+    /// Returns a `Grid` of the collapsed `waves`.
+    fn grid(&self, seed: u64, tiles: &Tiles, waves: &Waves) -> Grid {
+        let mut cells = Vec::new();
+        for wave_tiles in waves.tiles() {
+            let tile = wave_tiles.iter().next().unwrap();
+            cells.push(Cell {
+                tile: tile,
+                name: tiles.names()[tile].clone(),
+                rotation: tiles.rotations()[tile].clone(),
+                orientation: tiles.orientations()[tile],
+            });
+        }
+        return Grid::new(self.width, self.depth, self.height, seed, cells);
     }
 
     /// Returns valid wave edges for an coordinate in the wave graph.
     fn wave_edges(&self, x: usize, y: usize, z: usize) -> Vec<(usize, Face)> {
         let mut edges = Vec::new();
-        if x > 0 {
-            let edge_wave = self.edge_wave(x - 1, y, z);
-            edges.push((edge_wave, Face::Left));
-        }
-        if x < self.width - 1 {
-            let edge_wave = self.edge_wave(x + 1, y, z);
-            edges.push((edge_wave, Face::Right));
-        }
-        if y > 0 {
-            let edge_wave = self.edge_wave(x, y - 1, z);
-            edges.push((edge_wave, Face::Front));
-        }
-        if y < self.depth - 1 {
-            let edge_wave = self.edge_wave(x, y + 1, z);
-            edges.push((edge_wave, Face::Back));
-        }
-        if z > 0 {
-            let edge_wave = self.edge_wave(x, y, z - 1);
-            edges.push((edge_wave, Face::Down));
-        }
-        if z < self.height - 1 {
-            let edge_wave = self.edge_wave(x, y, z + 1);
-            edges.push((edge_wave, Face::Up));
+        for face in Face::ALL {
+            if let Some((x, y, z)) = self.neighbor((x, y, z), &face) {
+                edges.push((self.edge_wave(x, y, z), face));
+            }
         }
         return edges;
     }
 
+    /// Returns the coordinate across `face` from `coordinate`, wrapping
+    /// around periodic boundaries, or `None` outside the grid.
+    fn neighbor(&self, coordinate: Coordinate, face: &Face) -> Option<Coordinate> {
+        if self.is_on_face(coordinate, face) && self.boundaries[face.index()] != Boundary::Periodic {
+            return None;
+        }
+        let size = [self.width, self.depth, self.height];
+        let (x, y, z) = coordinate;
+        let normal = face.normal();
+        let [x, y, z] = [0, 1, 2].map(|axis| {
+            let position = [x, y, z][axis] as i64 + normal[axis] as i64;
+            position.rem_euclid(size[axis] as i64) as usize
+        });
+        return Some((x, y, z));
+    }
+
+    /// Returns the coordinate of a given wave.
+    fn coordinate(&self, wave: usize) -> Coordinate {
+        let layer_size = self.width * self.depth;
+        return (wave % self.width, wave % layer_size / self.width, wave / layer_size);
+    }
+
     /// Returns the wave for a given coordinate.
     fn edge_wave(&self, x: usize, y: usize, z: usize) -> usize {
         return x + (y * self.width) + (z * self.width * self.depth);
     }
 
-    /// Write a MagicaVoxel Viewer mv_import file to render the final waves.
-    pub fn render(
-        &self,
-        tile_size: usize,
-        vox_paths: &Vec<PathBuf>,
-        coordinates: Vec<(usize, usize, usize)>,
-        tiles: &Vec<HashSet<usize>>,
-    ) {
-        let file = File::create(&self.output_file).expect("Unable to create vox viewer file");
+    /// Returns the names of the axes the grid wraps around.
+    fn periodic_axes(&self) -> Vec<&'static str> {
+        return Vec::from_iter(
+            [("x", Face::Right), ("y", Face::Back), ("z", Face::Up)]
+                .into_iter()
+                .filter(|(_, face)| self.boundaries[face.index()] == Boundary::Periodic)
+                .map(|(axis, _)| axis),
+        );
+    }
+
+    /// Renders `grid` to the output file in the output format.
+    fn output(&self, grid: &Grid, tiles: &Tiles) -> Result<()> {
+        return match self.output_format {
+            OutputFormat::MvImport => self.render(grid, tiles.size(), &self.vox_paths(tiles)?),
+            OutputFormat::Vox => self.render_vox(grid, tiles.size(), tiles.voxes()),
+        };
+    }
+
+    /// Returns the file of each tile, writing tiles without a source file to
+    /// the cache directory.
+    fn vox_paths(&self, tiles: &Tiles) -> Result<Vec<PathBuf>> {
+        let cache_dir = match &self.cache_dir {
+            Some(cache_dir) => PathBuf::from(cache_dir),
+            None => {
+                let output_dir = Path::new(&self.output_file).parent().unwrap_or(Path::new(""));
+                output_dir.join("tiles")
+            }
+        };
+        let mut vox_paths = Vec::new();
+        for (tile, vox) in tiles.voxes().iter().enumerate() {
+            match &tiles.vox_paths()[tile] {
+                Some(vox_path) => vox_paths.push(vox_path.clone()),
+                None => vox_paths.push(cache::vox_path(&cache_dir, &tiles.names()[tile], vox)?),
+            }
+        }
+        return Ok(vox_paths);
+    }
+
+    /// Write a MagicaVoxel Viewer mv_import file to render `grid`.
+    ///
+    /// The seed that produced `grid` is recorded in the header so the
+    /// output can be regenerated, along with any axes it tiles seamlessly
+    /// along.
+    pub fn render(&self, grid: &Grid, tile_size: [usize; 3], vox_paths: &[PathBuf]) -> Result<()> {
+        if let Some(output_dir) = Path::new(&self.output_file).parent() {
+            fs::create_dir_all(output_dir)?;
+        }
+        let file = File::create(&self.output_file)?;
         let mut writer = BufWriter::new(file);
-        writer
-            .write("// Generated wfc output\n".as_bytes())
-            .unwrap();
-        let max_dimension_size = std::cmp::max(self.width, std::cmp::max(self.depth, self.height));
-        let mv_import_size = max_dimension_size * tile_size;
+        writer.write_all("// Generated wfc output\n".as_bytes())?;
+        let seed_comment = format!("// seed {seed}\n", seed = grid.seed());
+        writer.write_all(seed_comment.as_bytes())?;
+        let periodic_axes = self.periodic_axes();
+        if !periodic_axes.is_empty() {
+            let periodic_comment = format!("// periodic {}\n", periodic_axes.join(" "));
+            writer.write_all(periodic_comment.as_bytes())?;
+        }
+        let size = [grid.width(), grid.depth(), grid.height()];
+        let mv_import_size = (0..3).map(|axis| size[axis] * tile_size[axis]).max().unwrap();
         let header = format!(
             "mv_import {mv_import_size}\n",
             mv_import_size = mv_import_size
         );
-        writer.write(header.as_bytes()).unwrap();
-        for ((x, y, z), tiles) in coordinates.iter().zip(tiles) {
-            let x = x * tile_size;
-            let y = y * tile_size;
-            let z = z * tile_size;
-            for tile in tiles.iter() {
-                let path = vox_paths[*tile].clone();
-                let absolute_path = path.canonicalize().unwrap();
-                let absolute_path_str = absolute_path.to_str().unwrap();
-                let tile = format!(
-                    "{x} {y} {z} {path}\n",
-                    x = x,
-                    y = y,
-                    z = z,
-                    path = absolute_path_str
-                );
-                writer.write(tile.as_bytes()).unwrap();
+        writer.write_all(header.as_bytes())?;
+        for ((x, y, z), cell) in grid.iter() {
+            let x = x * tile_size[0];
+            let y = y * tile_size[1];
+            let z = z * tile_size[2];
+            let path = vox_paths[cell.tile].clone();
+            let absolute_path = path.canonicalize()?;
+            let absolute_path_str = absolute_path.to_string_lossy();
+            let tile = format!(
+                "{x} {y} {z} {path}\n",
+                x = x,
+                y = y,
+                z = z,
+                path = absolute_path_str
+            );
+            writer.write_all(tile.as_bytes())?;
+        }
+        writer.flush()?;
+        return Ok(());
+    }
+
+    /// Write a single VOX file with the voxels of every tile of `grid`.
+    ///
+    /// The palettes of all tiles are merged, and any color approximated to
+    /// fit the merged palette is reported to the observer. Materials are
+    /// taken from the tile of the first cell. The seed of `grid` and any
+    /// periodic axes are recorded as `_seed` and `_periodic` attributes on
+    /// the root node.
+    pub fn render_vox(&self, grid: &Grid, tile_size: [usize; 3], voxes: &[Vox]) -> Result<()> {
+        let first_cell = grid.cells().first().ok_or(Error::EmptyGrid)?;
+        let palette = MergedPalette::new(voxes);
+        for approximation in palette.approximations() {
+            self.observer.approximation(approximation);
+        }
+        let voxes = Vec::from_iter(voxes.iter().enumerate().map(|(i, vox)| palette.apply(i, vox)));
+        let mut voxels = Vec::new();
+        for ((x, y, z), cell) in grid.iter() {
+            for xyzi in voxes[cell.tile].xyzis() {
+                let coordinate = [
+                    x * tile_size[0] + xyzi[0] as usize,
+                    y * tile_size[1] + xyzi[1] as usize,
+                    z * tile_size[2] + xyzi[2] as usize,
+                ];
+                voxels.push((coordinate, xyzi[3]));
             }
         }
+        let cells = [grid.width(), grid.depth(), grid.height()];
+        let size = [0, 1, 2].map(|axis| cells[axis] * tile_size[axis]);
+        let mut vox = voxes[first_cell.tile].with_voxels(size, voxels);
+        vox.set_root_attribute("_seed", &grid.seed().to_string());
+        let periodic_axes = self.periodic_axes();
+        if !periodic_axes.is_empty() {
+            vox.set_root_attribute("_periodic", &periodic_axes.join(" "));
+        }
+        if let Some(output_dir) = Path::new(&self.output_file).parent() {
+            fs::create_dir_all(output_dir)?;
+        }
+        return vox.write(&self.output_file);
     }
 }
@@ -0,0 +1,87 @@
+use std::rc::Rc;
+
+use super::palette::Approximation;
+use super::wave::Contradiction;
+
+/// Hooks called while `wfc` collapses a wave graph and renders the output.
+///
+/// Every method does nothing by default, so implementations only override
+/// the events they care about. `()` is the silent observer used unless
+/// `Model::set_observer` is called.
+pub trait Observer {
+    /// Called after `tile` is chosen for `wave`.
+    fn observe(&self, _wave: usize, _tile: usize) {}
+
+    /// Called after the observation of `wave` is propogated without a contradiction.
+    fn propagate(&self, _wave: usize) {}
+
+    /// Called when propogating an observation fails, before any backtracking.
+    fn contradiction(&self, _contradiction: &Contradiction) {}
+
+    /// Called each time the whole percentage of collapsed waves increases.
+    fn progress(&self, _percent: usize) {}
+
+    /// Called when a tile color is replaced to fit a merged output palette.
+    fn approximation(&self, _approximation: &Approximation) {}
+}
+
+impl Observer for () {}
+
+/// Forwards to the shared observer, so callers can keep a handle to inspect it.
+impl<T: Observer + ?Sized> Observer for Rc<T> {
+    fn observe(&self, wave: usize, tile: usize) {
+        (**self).observe(wave, tile);
+    }
+
+    fn propagate(&self, wave: usize) {
+        (**self).propagate(wave);
+    }
+
+    fn contradiction(&self, contradiction: &Contradiction) {
+        (**self).contradiction(contradiction);
+    }
+
+    fn progress(&self, percent: usize) {
+        (**self).progress(percent);
+    }
+
+    fn approximation(&self, approximation: &Approximation) {
+        (**self).approximation(approximation);
+    }
+}
+
+#[cfg(feature = "log")]
+#[derive(Debug, Default, Clone, Copy)]
+/// An `Observer` that reports events through the `log` crate.
+///
+/// Progress is logged at info level, contradictions and approximated colors
+/// at warn, observations at debug and propogations at trace.
+pub struct LogObserver;
+
+#[cfg(feature = "log")]
+impl Observer for LogObserver {
+    fn observe(&self, wave: usize, tile: usize) {
+        log::debug!("observed tile {} at wave {}", tile, wave);
+    }
+
+    fn propagate(&self, wave: usize) {
+        log::trace!("propogated wave {}", wave);
+    }
+
+    fn contradiction(&self, c: &Contradiction) {
+        log::warn!(
+            "contradiction at wave {} on face {:?} given tiles {:?}",
+            c.wave,
+            c.face,
+            c.names
+        );
+    }
+
+    fn progress(&self, percent: usize) {
+        log::info!("{}% collapsed", percent);
+    }
+
+    fn approximation(&self, a: &Approximation) {
+        log::warn!("approximated color {:?} with {:?}", a.color, a.replacement);
+    }
+}
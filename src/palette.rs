@@ -0,0 +1,162 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use super::vox::{Palette, Vox};
+
+/// The number of colors a palette holds, as color index 0 is empty.
+const COLOR_COUNT: usize = 255;
+
+type Rgba = [u8; 4];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A color that did not fit in a merged palette and the color replacing it.
+pub struct Approximation {
+    pub color: Rgba,
+    pub replacement: Rgba,
+}
+
+#[derive(Debug)]
+/// A palette of the colors used by several `Vox` and the mapping of each
+/// `Vox` color index into it.
+pub struct MergedPalette {
+    palette: Palette,
+    remaps: Vec<[u8; 256]>,
+    approximations: Vec<Approximation>,
+}
+
+/// Returns the squared distance between two colors.
+fn distance(a: &Rgba, b: &Rgba) -> u32 {
+    return a
+        .iter()
+        .zip(b)
+        .map(|(a, b)| (*a as i32 - *b as i32).pow(2) as u32)
+        .sum();
+}
+
+impl MergedPalette {
+    /// Merges the colors used by the voxels of `voxes`.
+    ///
+    /// Identical colors share a color index. If more than 255 colors remain
+    /// the most used are kept, and every other color is approximated by the
+    /// nearest kept color.
+    pub fn new(voxes: &[Vox]) -> Self {
+        let mut colors = Vec::new();
+        let mut counts = HashMap::new();
+        for vox in voxes.iter() {
+            for (index, count) in vox.color_counts().iter().enumerate() {
+                if *count == 0 {
+                    continue;
+                }
+                let color = vox.palette()[index];
+                let color_count = counts.entry(color).or_insert(0);
+                if *color_count == 0 {
+                    colors.push(color);
+                }
+                *color_count += count;
+            }
+        }
+
+        // A stable sort keeps the first seen of equally used colors
+        let mut kept = colors.clone();
+        if kept.len() > COLOR_COUNT {
+            kept.sort_by_key(|color| Reverse(counts[color]));
+            kept.truncate(COLOR_COUNT);
+        }
+        let mut palette = [[0; 4]; 256];
+        let mut indices = HashMap::new();
+        for (i, color) in kept.iter().enumerate() {
+            palette[i + 1] = *color;
+            indices.insert(*color, i as u8 + 1);
+        }
+
+        let mut approximations = Vec::new();
+        for color in colors.iter() {
+            if indices.contains_key(color) {
+                continue;
+            }
+            let replacement = *kept
+                .iter()
+                .min_by_key(|kept_color| distance(color, kept_color))
+                .unwrap();
+            indices.insert(*color, indices[&replacement]);
+            approximations.push(Approximation {
+                color: *color,
+                replacement: replacement,
+            });
+        }
+
+        let mut remaps = Vec::new();
+        for vox in voxes.iter() {
+            let mut remap = [0; 256];
+            for (index, count) in vox.color_counts().iter().enumerate() {
+                if *count > 0 {
+                    remap[index] = indices[&vox.palette()[index]];
+                }
+            }
+            remaps.push(remap);
+        }
+        return Self {
+            palette: palette,
+            remaps: remaps,
+            approximations: approximations,
+        };
+    }
+
+    /// Returns `vox`, the `i`th of the merged `Vox`, recolored with the merged palette.
+    pub fn apply(&self, i: usize, vox: &Vox) -> Vox {
+        return vox.remapped(&self.palette, &self.remaps[i]);
+    }
+
+    /// Returns the colors that were replaced by a similar color.
+    pub fn approximations(&self) -> &[Approximation] {
+        return &self.approximations;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a `Vox` with a voxel of each of `colors` from `palette`.
+    fn vox(colors: std::ops::Range<usize>, palette: &Palette) -> Vox {
+        let vox = Vox::open("tests/samples/stairs/stairs-0-empty.vox").unwrap();
+        let voxels = colors.map(|color| ([color, 0, 0], color as u8));
+        let identity = std::array::from_fn(|i| i as u8);
+        return vox.with_voxels([256, 1, 1], voxels).remapped(palette, &identity);
+    }
+
+    /// Returns a palette where every color is distinct.
+    fn distinct_palette() -> Palette {
+        return std::array::from_fn(|i| [i as u8, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_exact_merge() {
+        let palette = distinct_palette();
+        let voxes = [vox(1..100, &palette), vox(50..200, &palette)];
+        let merged = MergedPalette::new(&voxes);
+        assert!(merged.approximations().is_empty());
+        for (i, vox) in voxes.iter().enumerate() {
+            assert_eq!(merged.apply(i, vox).palette(), &merged.palette);
+        }
+        assert_eq!(merged.remaps[0][60], merged.remaps[1][60]);
+        assert_eq!(merged.palette[merged.remaps[1][150] as usize], palette[150]);
+    }
+
+    #[test]
+    fn test_approximate_merge() {
+        let palette = distinct_palette();
+        let mut other_palette = palette;
+        other_palette[1] = [1, 0, 1, 255];
+        let voxes = [vox(1..256, &palette), vox(1..2, &other_palette)];
+        let merged = MergedPalette::new(&voxes);
+        assert_eq!(
+            merged.approximations(),
+            &[Approximation {
+                color: other_palette[1],
+                replacement: palette[1],
+            }]
+        );
+        assert_eq!(merged.remaps[1][1], merged.remaps[0][1]);
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use super::model::Face;
+use super::wave::Contradiction;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// How the seed of each retry is derived from the previous attempt.
+pub enum SeedDerivation {
+    /// Each seed is drawn from an RNG seeded with the previous seed, so any
+    /// recorded seed replays the attempts that follow it.
+    Chained,
+    /// Each seed is the initial seed plus the attempt number.
+    Sequential,
+}
+
+impl SeedDerivation {
+    /// Returns the seed of `attempt` given the initial and previous seeds.
+    pub fn seed(&self, initial_seed: u64, previous_seed: u64, attempt: usize) -> u64 {
+        return match self {
+            SeedDerivation::Chained => ChaCha8Rng::seed_from_u64(previous_seed).next_u64(),
+            SeedDerivation::Sequential => initial_seed.wrapping_add(attempt as u64),
+        };
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Limits on how often `wfc` is retried after a contradiction.
+pub struct RetryPolicy {
+    /// The maximum number of attempts, or `None` to retry indefinitely.
+    pub max_attempts: Option<usize>,
+    /// The wall-clock time after which no new attempt is started.
+    pub time_budget: Option<Duration>,
+    pub seed_derivation: SeedDerivation,
+}
+
+impl RetryPolicy {
+    /// Constructs a `RetryPolicy`.
+    pub fn new(
+        max_attempts: Option<usize>,
+        time_budget: Option<Duration>,
+        seed_derivation: SeedDerivation,
+    ) -> Self {
+        return Self {
+            max_attempts: max_attempts,
+            time_budget: time_budget,
+            seed_derivation: seed_derivation,
+        };
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Retries indefinitely with chained seeds.
+    fn default() -> Self {
+        return Self::new(None, None, SeedDerivation::Chained);
+    }
+}
+
+#[derive(Debug, Default)]
+/// The seed and contradiction of every failed attempt.
+pub struct Attempts {
+    attempts: Vec<(u64, Contradiction)>,
+}
+
+impl Attempts {
+    /// Records a failed attempt.
+    pub fn push(&mut self, seed: u64, contradiction: Contradiction) {
+        self.attempts.push((seed, contradiction));
+    }
+
+    /// Returns the number of failed attempts.
+    pub fn len(&self) -> usize {
+        return self.attempts.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.attempts.is_empty();
+    }
+
+    /// Returns the seed and contradiction of each failed attempt in order.
+    pub fn iter(&self) -> impl Iterator<Item = &(u64, Contradiction)> {
+        return self.attempts.iter();
+    }
+
+    /// Returns each wave a contradiction started from with its count, most frequent first.
+    pub fn wave_counts(&self) -> Vec<(usize, usize)> {
+        let mut counts = HashMap::new();
+        for (_, contradiction) in self.attempts.iter() {
+            *counts.entry(contradiction.wave).or_insert(0) += 1;
+        }
+        let mut counts = Vec::from_iter(counts);
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        return counts;
+    }
+
+    /// Returns each face a contradiction occurred on with its count, most frequent first.
+    pub fn face_counts(&self) -> Vec<(Face, usize)> {
+        let mut counts: Vec<(Face, usize)> = Vec::new();
+        for (_, contradiction) in self.attempts.iter() {
+            match counts.iter_mut().find(|(face, _)| *face == contradiction.face) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((contradiction.face.clone(), 1)),
+            }
+        }
+        counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        return counts;
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fs;
@@ -5,19 +6,265 @@ use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 
+use super::bitset::BitSet;
+use super::error::{Error, Result};
 use super::model::Face;
+use super::validation::{AsymmetricAdjacency, DeadFace, OneSidedConnector, TileName, ValidationReport};
 use super::vox::Vox;
 
 #[derive(Debug, Serialize, Deserialize)]
 struct Config {
-    tile_size: usize,
+    tile_size: TileSize,
+    /// A multi-model VOX file in the sample directory holding every tile.
+    #[serde(default)]
+    tileset: Option<String>,
+    /// The transforms variants are inferred from for tiles without a `symmetry`.
+    #[serde(default)]
+    symmetry_group: SymmetryGroup,
     tile_configs: Vec<TileConfig>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(untagged)]
+/// The size of every tile, the same on each axis or given per axis.
+enum TileSize {
+    Cube(usize),
+    /// The x, y and z size.
+    Box([usize; 3]),
+}
+
+impl TileSize {
+    fn xyz(&self) -> [usize; 3] {
+        return match self {
+            TileSize::Cube(size) => [*size; 3],
+            TileSize::Box(size) => *size,
+        };
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+/// A group of transforms to infer the variants of a tile from.
+enum SymmetryGroup {
+    /// Rotations about the z axis and reflections, keeping tiles upright.
+    #[default]
+    Z,
+    /// The 24 rotations of the cube, which may tip tiles onto any face.
+    Cube,
+    /// The 48 rotations and reflections of the cube.
+    CubeReflections,
+}
+
+impl SymmetryGroup {
+    /// Returns the transforms of the group other than the identity.
+    fn transforms(&self) -> Vec<Transform> {
+        let transforms = Transform::all().into_iter();
+        return match self {
+            SymmetryGroup::Z => Vec::from_iter(transforms.take(5)),
+            SymmetryGroup::Cube => Vec::from_iter(transforms.filter(|t| t.reflection.is_none())),
+            // `fY` is the same transform as `R180fX`
+            SymmetryGroup::CubeReflections => Vec::from_iter(transforms.filter(|t| t.name() != "fY")),
+        };
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct TileConfig {
     name: String,
     connectors: Connectors,
+    /// The relative frequency of the tile and its generated variants.
+    #[serde(default = "default_weight")]
+    weight: f64,
+    /// Weights overriding `weight` for generated variants, keyed by transform
+    /// such as `R90` or `fX`.
+    #[serde(default)]
+    variant_weights: HashMap<String, f64>,
+    /// The model of the tile in the tileset, which defaults to the nTRN node
+    /// named after the tile.
+    #[serde(default)]
+    model: Option<TileModel>,
+    /// The variants to generate, which are inferred from the connectors if
+    /// not given.
+    #[serde(default)]
+    symmetry: Option<TileSymmetry>,
+    /// Labels to select the tile and its variants by, such as in pins.
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+/// The variants generated for a tile.
+enum TileSymmetry {
+    Class(SymmetryClass),
+    /// Transforms such as `R90` or `fX`.
+    Transforms(Vec<String>),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+/// A standard WFC symmetry class, named after the letter with the same
+/// symmetries about the z axis.
+enum SymmetryClass {
+    /// Unchanged by any transform, so no variants are generated.
+    X,
+    /// Two variants.
+    I,
+    /// Two variants, symmetric about a diagonal.
+    #[serde(rename = "\\")]
+    Backslash,
+    /// Four variants, symmetric about one axis.
+    T,
+    /// Four variants, symmetric about a diagonal.
+    L,
+    /// Eight variants, with no symmetry.
+    F,
+}
+
+impl SymmetryClass {
+    /// Returns the names of the transforms generating the variants of the class.
+    fn transforms(&self) -> &'static [&'static str] {
+        return match self {
+            SymmetryClass::X => &[],
+            SymmetryClass::I | SymmetryClass::Backslash => &["R90"],
+            SymmetryClass::T | SymmetryClass::L => &["R90", "R180", "R270"],
+            SymmetryClass::F => &["R90", "R180", "R270", "fX", "fY", "R90fX", "R270fX"],
+        };
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+/// A reference to a model in a multi-model tileset.
+enum TileModel {
+    /// The index of the model in the tileset.
+    Index(usize),
+    /// The `_name` of the nTRN node that places the model.
+    Name(String),
+}
+
+fn default_weight() -> f64 {
+    return 1.0;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A turn about the x or y axis that tips a tile onto another face.
+enum Tilt {
+    None,
+    X90,
+    X180,
+    X270,
+    Y90,
+    Y270,
+}
+
+impl Tilt {
+    /// Every tilt, one for each face that can end up on top.
+    const ALL: [Tilt; 6] = [Tilt::None, Tilt::X90, Tilt::X180, Tilt::X270, Tilt::Y90, Tilt::Y270];
+
+    fn name(&self) -> &'static str {
+        return match self {
+            Tilt::None => "",
+            Tilt::X90 => "RX90",
+            Tilt::X180 => "RX180",
+            Tilt::X270 => "RX270",
+            Tilt::Y90 => "RY90",
+            Tilt::Y270 => "RY270",
+        };
+    }
+
+    fn orientation(&self) -> Orientation {
+        return match self {
+            Tilt::None => Orientation::IDENTITY,
+            Tilt::X90 => Orientation::turns(0, 1),
+            Tilt::X180 => Orientation::turns(0, 2),
+            Tilt::X270 => Orientation::turns(0, 3),
+            Tilt::Y90 => Orientation::turns(1, 1),
+            Tilt::Y270 => Orientation::turns(1, 3),
+        };
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A transform generating a variant of a tile, tilting it, rotating it about
+/// the z axis and then reflecting it.
+struct Transform {
+    tilt: Tilt,
+    rotation: Rotation,
+    reflection: Option<Axis>,
+}
+
+impl Transform {
+    /// Every transform that generates a variant, in generation order.
+    ///
+    /// The upright transforms come first, followed by those that tilt a tile.
+    fn all() -> Vec<Transform> {
+        let transform = |tilt, rotation, reflection| Transform {
+            tilt: tilt,
+            rotation: rotation,
+            reflection: reflection,
+        };
+        let mut transforms = vec![
+            transform(Tilt::None, Rotation::R90, None),
+            transform(Tilt::None, Rotation::R180, None),
+            transform(Tilt::None, Rotation::R270, None),
+            transform(Tilt::None, Rotation::R0, Some(Axis::X)),
+            transform(Tilt::None, Rotation::R0, Some(Axis::Y)),
+            transform(Tilt::None, Rotation::R90, Some(Axis::X)),
+            transform(Tilt::None, Rotation::R270, Some(Axis::X)),
+            transform(Tilt::None, Rotation::R180, Some(Axis::X)),
+        ];
+        for tilt in Tilt::ALL.into_iter().skip(1) {
+            for reflection in [None, Some(Axis::X)] {
+                for rotation in [Rotation::R0, Rotation::R90, Rotation::R180, Rotation::R270] {
+                    transforms.push(transform(tilt, rotation, reflection.clone()));
+                }
+            }
+        }
+        return transforms;
+    }
+
+    /// Returns the transform named `name`, such as `R90`, `fX`, `R90fX` or `RX90R180`.
+    fn from_name(name: &str) -> Option<Self> {
+        return Transform::all().into_iter().find(|transform| transform.name() == name);
+    }
+
+    /// Returns the name of the transform, as used in `variant_weights`.
+    fn name(&self) -> String {
+        let mut name = String::from(self.tilt.name());
+        if self.rotation != Rotation::R0 {
+            name.push_str(&format!("{:?}", self.rotation));
+        }
+        if let Some(axis) = &self.reflection {
+            name.push_str(&format!("f{:?}", axis));
+        }
+        return name;
+    }
+
+    /// Returns true if the transform keeps a tile of `size` the same size.
+    fn fits(&self, size: [usize; 3]) -> bool {
+        let size = size.map(|axis_size| axis_size as i32);
+        return self.orientation().apply(size).map(i32::abs) == size;
+    }
+
+    /// Returns the rotation of the transform, without its reflection.
+    fn orientation(&self) -> Orientation {
+        return self.tilt.orientation().then(&Orientation::from(&self.rotation));
+    }
+
+    fn connectors(&self, connectors: &Connectors) -> Connectors {
+        let connectors = connectors.rotated(&self.orientation());
+        return match &self.reflection {
+            Some(axis) => connectors.reflected(axis),
+            None => connectors,
+        };
+    }
+
+    fn vox(&self, vox: &Vox) -> Vox {
+        let vox = vox.oriented(&self.orientation());
+        return match &self.reflection {
+            Some(axis) => vox.reflected(axis),
+            None => vox,
+        };
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
@@ -37,6 +284,26 @@ struct Connector {
 }
 
 impl Connector {
+    /// Returns true if this connector, on `face` of a tile and turned by
+    /// `twist`, fits `neighbor` on the opposite face of the next tile, turned
+    /// by `neighbor_twist`.
+    ///
+    /// The rule is symmetric, so `neighbor` fits this connector from the
+    /// opposite face exactly when this connector fits `neighbor`.
+    fn fits(&self, face: &Face, twist: &Rotation, neighbor: &Connector, neighbor_twist: &Rotation) -> bool {
+        let is_vertical = *face == Face::Down || *face == Face::Up;
+        let twist_fits = twist == neighbor_twist;
+        let symmetry_fits = match (&self.symmetry, &neighbor.symmetry) {
+            (Symmetry::Symmetrical, Symmetry::Symmetrical) => true,
+            (Symmetry::Symmetrical, _) | (_, Symmetry::Symmetrical) => false,
+            // Vertically, Normal fits Inverse at any twist
+            (Symmetry::Normal, Symmetry::Inverse) | (Symmetry::Inverse, Symmetry::Normal) if is_vertical => true,
+            (a, b) if is_vertical => a == b && twist_fits,
+            (a, b) => a != b && twist_fits,
+        };
+        return self.id == neighbor.id && symmetry_fits;
+    }
+
     fn inverse(&self) -> Self {
         let id = self.id;
         let symmetry = match self.symmetry{
@@ -59,41 +326,29 @@ enum Symmetry {
 }
 
 impl Connectors {
-    /// Returns a new `Connectors` rotated `rotation` degrees about the z axis
-    fn rotated(&self, rotation: &Rotation) -> Self {
-        return match rotation {
-            Rotation::R0 => Self {
-                left: self.left.clone(),
-                right: self.right.clone(),
-                front: self.front.clone(),
-                back: self.back.clone(),
-                down: self.down.clone(),
-                up: self.up.clone(),
-            },
-            Rotation::R90 => Self {
-                left: self.back.clone(),
-                right: self.front.clone(),
-                front: self.left.clone(),
-                back: self.right.clone(),
-                down: self.down.clone(),
-                up: self.up.clone(),
-            },
-            Rotation::R180 => Self {
-                left: self.right.clone(),
-                right: self.left.clone(),
-                front: self.back.clone(),
-                back: self.front.clone(),
-                down: self.down.clone(),
-                up: self.up.clone(),
-            },
-            Rotation::R270 => Self {
-                left: self.front.clone(),
-                right: self.back.clone(),
-                front: self.right.clone(),
-                back: self.left.clone(),
-                down: self.down.clone(),
-                up: self.up.clone(),
-            },
+    /// Returns a new `Connectors` turned by `orientation`.
+    ///
+    /// Connectors are labelled as seen from outside the tile, except on the
+    /// down face, which is labelled as seen from above like the up face so
+    /// that `Normal` stacks on `Normal`. A connector turned onto or off the
+    /// down face is therefore seen mirrored and swaps `Normal` and `Inverse`.
+    fn rotated(&self, orientation: &Orientation) -> Self {
+        let inverse = orientation.inverse();
+        let source = |face: Face| {
+            let source_face = Face::towards(inverse.apply(face.normal())).unwrap();
+            let connector = self.get(&source_face).clone();
+            if (source_face == Face::Down) != (face == Face::Down) {
+                return connector.inverse();
+            }
+            return connector;
+        };
+        return Self {
+            left: source(Face::Left),
+            right: source(Face::Right),
+            front: source(Face::Front),
+            back: source(Face::Back),
+            down: source(Face::Down),
+            up: source(Face::Up),
         };
     }
 
@@ -135,10 +390,28 @@ impl Connectors {
 #[derive(Debug)]
 /// A container for tile data provided in a sample directory.
 pub struct Tiles {
-    size: usize,
-    vox_paths: Vec<PathBuf>,
+    /// The x, y and z size of every tile.
+    size: [usize; 3],
+    names: Vec<String>,
+    voxes: Vec<Vox>,
+    /// The source file of each tile, or `None` for tiles from a tileset or
+    /// generated in memory.
+    vox_paths: Vec<Option<PathBuf>>,
+    /// The rotation of each tile's up face about the z axis.
     rotations: Vec<Rotation>,
+    orientations: Vec<Orientation>,
     connectors: Vec<Connectors>,
+    weights: Vec<f64>,
+    variant_weights: Vec<HashMap<String, f64>>,
+    /// The transforms of each tile to generate variants with, or `None` to
+    /// infer them from the connectors.
+    symmetries: Vec<Option<Vec<Transform>>>,
+    symmetry_group: SymmetryGroup,
+    /// The config tile each tile is, or was generated from.
+    sources: Vec<usize>,
+    /// The tags of each config tile, shared by its variants.
+    tags: Vec<Vec<String>>,
+    tileset_path: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -157,139 +430,349 @@ pub enum Axis {
     Y,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// A rotation mapping a cube onto itself, following the right-hand rule.
+pub struct Orientation {
+    /// The rotation matrix, whose rows each have a single 1 or -1.
+    matrix: [[i32; 3]; 3],
+}
+
+impl Orientation {
+    /// The orientation of an untransformed tile.
+    pub const IDENTITY: Orientation = Orientation {
+        matrix: [[1, 0, 0], [0, 1, 0], [0, 0, 1]],
+    };
+
+    /// Returns `count` quarter turns about `axis`, where 0, 1 and 2 are x, y and z.
+    fn turns(axis: usize, count: usize) -> Self {
+        let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+        let mut matrix = [[0; 3]; 3];
+        matrix[axis][axis] = 1;
+        matrix[v][u] = 1;
+        matrix[u][v] = -1;
+        let quarter_turn = Orientation { matrix: matrix };
+        let mut orientation = Orientation::IDENTITY;
+        for _ in 0..count {
+            orientation = orientation.then(&quarter_turn);
+        }
+        return orientation;
+    }
+
+    /// Returns the rotation matrix mapping tile coordinates to world coordinates.
+    pub fn matrix(&self) -> [[i32; 3]; 3] {
+        return self.matrix;
+    }
+
+    /// Returns `vector` turned by this orientation.
+    pub fn apply(&self, vector: [i32; 3]) -> [i32; 3] {
+        return self.matrix.map(|row| row[0] * vector[0] + row[1] * vector[1] + row[2] * vector[2]);
+    }
+
+    /// Returns the orientation turning by this orientation and then by `other`.
+    pub fn then(&self, other: &Orientation) -> Self {
+        let mut matrix = [[0; 3]; 3];
+        for (i, row) in matrix.iter_mut().enumerate() {
+            for (j, entry) in row.iter_mut().enumerate() {
+                *entry = (0..3).map(|k| other.matrix[i][k] * self.matrix[k][j]).sum();
+            }
+        }
+        return Orientation { matrix: matrix };
+    }
+
+    /// Returns the orientation undoing this orientation.
+    pub fn inverse(&self) -> Self {
+        let mut matrix = [[0; 3]; 3];
+        for (i, row) in matrix.iter_mut().enumerate() {
+            for (j, entry) in row.iter_mut().enumerate() {
+                *entry = self.matrix[j][i];
+            }
+        }
+        return Orientation { matrix: matrix };
+    }
+
+    /// Returns how far this orientation turns the connector it moves onto
+    /// `face`, about the normal of `face`.
+    ///
+    /// Each face measures turns from its own first axis, z for side faces and
+    /// x for the up and down faces, so opposite faces measure alike and z
+    /// rotations leave side faces unturned.
+    fn twist(&self, face: &Face) -> Rotation {
+        let face_axes = |normal: [i32; 3]| match normal {
+            [0, 0, _] => ([1, 0, 0], [0, 1, 0]),
+            [_, 0, 0] => ([0, 0, 1], [0, 1, 0]),
+            _ => ([0, 0, 1], [1, 0, 0]),
+        };
+        let (source_u, _) = face_axes(self.inverse().apply(face.normal()));
+        let (u, v) = face_axes(face.normal());
+        let turned_u = self.apply(source_u);
+        return if turned_u == u {
+            Rotation::R0
+        } else if turned_u == v {
+            Rotation::R90
+        } else if turned_u == u.map(|c| -c) {
+            Rotation::R180
+        } else {
+            Rotation::R270
+        };
+    }
+}
+
+impl From<&Rotation> for Orientation {
+    fn from(rotation: &Rotation) -> Self {
+        return match rotation {
+            Rotation::R0 => Orientation::IDENTITY,
+            Rotation::R90 => Orientation::turns(2, 1),
+            Rotation::R180 => Orientation::turns(2, 2),
+            Rotation::R270 => Orientation::turns(2, 3),
+        };
+    }
+}
+
+/// Returns true if `weight` can be used as a relative tile frequency.
+fn is_valid_weight(weight: f64) -> bool {
+    return weight.is_finite() && weight > 0.0;
+}
+
+/// Returns the model of `tile_config` in `tileset`.
+fn extract_tile(tileset: &Vox, tile_config: &TileConfig) -> Result<Vox> {
+    let index = match &tile_config.model {
+        Some(TileModel::Index(index)) => Some(*index),
+        Some(TileModel::Name(name)) => tileset.model_index(name),
+        None => tileset.model_index(&tile_config.name),
+    };
+    let vox = index.and_then(|index| tileset.model(index)).ok_or_else(|| {
+        Error::InvalidConfig(format!(
+            "tile {:?} has no model {:?} in the tileset",
+            tile_config.name, tile_config.model
+        ))
+    })?;
+    return Ok(vox);
+}
+
 impl Tiles {
     /// Returns a new `Tiles` based on the config in `sample_dir`.
-    pub fn from(sample_dir: &str) -> std::io::Result<Self> {
+    pub fn from(sample_dir: &str) -> Result<Self> {
         let sample_dir = Path::new(sample_dir).to_path_buf();
         let config_path = sample_dir.join("config.json");
-        let config_json = fs::read_to_string(config_path).unwrap();
-        let config = serde_json::from_str::<Config>(&config_json).unwrap();
+        let config_json = fs::read_to_string(config_path)?;
+        let config = serde_json::from_str::<Config>(&config_json)?;
+        if config.tile_configs.is_empty() {
+            return Err(Error::InvalidConfig(String::from("the config has no tiles")));
+        }
+        let mut names = Vec::new();
+        let mut voxes = Vec::new();
         let mut vox_paths = Vec::new();
         let mut rotations = Vec::new();
+        let mut orientations = Vec::new();
         let mut connectors = Vec::new();
+        let mut weights = Vec::new();
+        let mut variant_weights = Vec::new();
+        let mut symmetries = Vec::new();
+        let mut tags = Vec::new();
+        let tileset_path = config.tileset.as_ref().map(|tileset| sample_dir.join(tileset));
+        let tileset = match &tileset_path {
+            Some(tileset_path) => {
+                if !tileset_path.is_file() {
+                    return Err(Error::MissingTile(tileset_path.clone()));
+                }
+                Some(Vox::open(tileset_path)?)
+            }
+            None => None,
+        };
         for tile_config in config.tile_configs {
-            vox_paths.push(sample_dir.join(tile_config.name).with_extension("vox"));
+            let is_valid_name = !tile_config.name.is_empty()
+                && !tile_config.name.contains(['@', '/', '\\']);
+            if !is_valid_name {
+                return Err(Error::InvalidTileName(tile_config.name));
+            }
+            let (vox, vox_path) = match &tileset {
+                Some(tileset) => (extract_tile(tileset, &tile_config)?, None),
+                None => {
+                    if tile_config.model.is_some() {
+                        return Err(Error::InvalidConfig(format!(
+                            "tile {:?} has a model but the config has no tileset",
+                            tile_config.name
+                        )));
+                    }
+                    let vox_path = sample_dir.join(&tile_config.name).with_extension("vox");
+                    if !vox_path.is_file() {
+                        return Err(Error::MissingTile(vox_path));
+                    }
+                    (Vox::open(&vox_path)?, Some(vox_path))
+                }
+            };
+            let tile_size = config.tile_size.xyz();
+            if let Some(size) = vox.sizes().into_iter().find(|size| *size != tile_size) {
+                return Err(Error::InvalidConfig(format!(
+                    "tile {:?} has size {:?} but the tile size is {:?}",
+                    tile_config.name, size, tile_size
+                )));
+            }
+            if !is_valid_weight(tile_config.weight) {
+                return Err(Error::InvalidConfig(format!(
+                    "tile {:?} has invalid weight {}",
+                    tile_config.name, tile_config.weight
+                )));
+            }
+            for (transform, weight) in tile_config.variant_weights.iter() {
+                if Transform::from_name(transform).is_none() || !is_valid_weight(*weight) {
+                    return Err(Error::InvalidConfig(format!(
+                        "tile {:?} has invalid variant weight {:?}: {}",
+                        tile_config.name, transform, weight
+                    )));
+                }
+            }
+            let symmetry = match &tile_config.symmetry {
+                Some(TileSymmetry::Class(class)) => Some(Vec::from_iter(
+                    class.transforms().iter().filter_map(|name| Transform::from_name(name)),
+                )),
+                Some(TileSymmetry::Transforms(names)) => {
+                    let mut transforms = Vec::new();
+                    for name in names.iter() {
+                        let transform = Transform::from_name(name).ok_or_else(|| {
+                            Error::InvalidConfig(format!(
+                                "tile {:?} has invalid symmetry transform {:?}",
+                                tile_config.name, name
+                            ))
+                        })?;
+                        transforms.push(transform);
+                    }
+                    Some(transforms)
+                }
+                None => None,
+            };
+            let transforms = symmetry.iter().flatten();
+            if let Some(transform) = transforms.into_iter().find(|t| !t.fits(tile_size)) {
+                return Err(Error::InvalidConfig(format!(
+                    "tile {:?} has symmetry transform {:?} that changes its size",
+                    tile_config.name,
+                    transform.name()
+                )));
+            }
+            voxes.push(vox);
+            vox_paths.push(vox_path);
+            names.push(tile_config.name);
             rotations.push(Rotation::R0);
+            orientations.push(Orientation::IDENTITY);
             connectors.push(tile_config.connectors);
+            weights.push(tile_config.weight);
+            variant_weights.push(tile_config.variant_weights);
+            symmetries.push(symmetry);
+            tags.push(tile_config.tags);
         }
         let tiles = Self {
-            size: config.tile_size,
+            size: config.tile_size.xyz(),
+            sources: Vec::from_iter(0..names.len()),
+            tags: tags,
+            names: names,
+            voxes: voxes,
             vox_paths: vox_paths,
             rotations: rotations,
+            orientations: orientations,
             connectors: connectors,
+            weights: weights,
+            variant_weights: variant_weights,
+            symmetries: symmetries,
+            symmetry_group: config.symmetry_group,
+            tileset_path: tileset_path,
         };
         return Ok(tiles);
     }
 
     /// Generates transformed tiles and vox objects for each config tile.
+    ///
+    /// Each variant is named after its tile and transform, such as
+    /// `road_turn_low@R90fX`.
+    ///
+    /// Tiles with a `symmetry` get a variant for each of its transforms.
+    /// Otherwise a variant is generated for each transform of the symmetry
+    /// group that keeps the tile size and whose connectors differ from the
+    /// tile and the variants before it.
     pub fn generate_transformed_tiles(&mut self) {
-        let mut generated_count = 0;
         let mut generated = Vec::new();
-        for (vox_path, connectors) in self.vox_paths.iter().zip(&self.connectors) {
-            let vox = Vox::open(vox_path).unwrap();
-            let tile_name = vox_path // TODO: Generate tile name if this fails
-                .file_stem()
-                .unwrap()
-                .to_str()
-                .unwrap()
-                .split("-")
-                .collect::<Vec<&str>>()[2];
-            let vox_extension = vox_path.extension().unwrap();
+        for (tile, ((name, vox), connectors)) in self
+            .names
+            .iter()
+            .zip(&self.voxes)
+            .zip(&self.connectors)
+            .enumerate()
+        {
+            let transforms = match &self.symmetries[tile] {
+                Some(transforms) => transforms.clone(),
+                None => Vec::from_iter(
+                    self.symmetry_group
+                        .transforms()
+                        .into_iter()
+                        .filter(|transform| transform.fits(self.size)),
+                ),
+            };
             let mut visited = HashSet::new();
             visited.insert(connectors.clone());
-            let rotations = [Rotation::R90, Rotation::R180, Rotation::R270];
-            for generated_rotation in rotations {
-                let generated_connectors = connectors.rotated(&generated_rotation);
-                if !visited.contains(&generated_connectors) {
-                    visited.insert(generated_connectors.clone());
-                    let generated_tile_name = format!(
-                        "generated-{generated_count}-{tile_name}_{rotation:?}",
-                        generated_count = generated_count,
-                        tile_name = tile_name,
-                        rotation = generated_rotation,
-                    );
-                    let generated_vox_path = vox_path
-                        .with_file_name(generated_tile_name)
-                        .with_extension(vox_extension);
-                    let generated_vox = vox.rotated(&generated_rotation);
-                    generated_vox.write(&generated_vox_path).unwrap();
-                    generated_count += 1;
-                    generated.push((generated_vox_path, generated_rotation, generated_connectors));
-                }
-            }
-            let axes = [Axis::X, Axis::Y];
-            for generated_axis in axes {
-                let generated_connectors = connectors.reflected(&generated_axis);
-                if !visited.contains(&generated_connectors) {
-                    visited.insert(generated_connectors.clone());
-                    let generated_tile_name = format!(
-                        "generated-{generated_count}-{tile_name}_f{axis:?}",
-                        generated_count = generated_count,
-                        tile_name = tile_name,
-                        axis = generated_axis,
-                    );
-                    let generated_vox_path = vox_path
-                        .with_file_name(generated_tile_name)
-                        .with_extension(vox_extension);
-                    let generated_vox = vox.reflected(&generated_axis);
-                    generated_vox.write(&generated_vox_path).unwrap();
-                    generated_count += 1;
-                    generated.push((generated_vox_path, Rotation::R0, generated_connectors));
+            for transform in transforms {
+                let generated_connectors = transform.connectors(connectors);
+                let is_new = visited.insert(generated_connectors.clone());
+                if !is_new && self.symmetries[tile].is_none() {
+                    continue;
                 }
+                let generated_tile_name = format!("{}@{}", name, transform.name());
+                generated.push((
+                    tile,
+                    generated_tile_name,
+                    transform.vox(vox),
+                    transform.orientation(),
+                    generated_connectors,
+                    self.variant_weight(tile, &transform.name()),
+                ));
             }
         }
 
-        for (vox_path, rotation, connectors) in generated {
-            self.vox_paths.push(vox_path);
-            self.rotations.push(rotation);
+        for (source, name, vox, orientation, connectors, weight) in generated {
+            self.sources.push(source);
+            self.names.push(name);
+            self.voxes.push(vox);
+            self.vox_paths.push(None);
+            self.rotations.push(orientation.twist(&Face::Up));
+            self.orientations.push(orientation);
             self.connectors.push(connectors);
+            self.weights.push(weight);
+            self.variant_weights.push(HashMap::new());
+            self.symmetries.push(Some(Vec::new()));
         }
     }
 
+    /// Returns the weight of the variant of `tile` generated by `transform`.
+    fn variant_weight(&self, tile: usize, transform: &str) -> f64 {
+        return *self.variant_weights[tile]
+            .get(transform)
+            .unwrap_or(&self.weights[tile]);
+    }
+
     /// Returns valid tiles for each tile on each face to constrain `wfc`.
-    pub fn constraints(&self) -> HashMap<Face, Vec<HashSet<usize>>> {
+    ///
+    /// Asymmetric connectors only fit connectors turned the same way about
+    /// the face between them, which for upright tiles only matters on the
+    /// up and down faces. On the side faces `Normal` fits `Inverse`, and on
+    /// the up and down faces it fits `Normal`, while `Inverse` fits `Inverse`
+    /// or, turned any way, `Normal`.
+    pub fn constraints(&self) -> HashMap<Face, Vec<BitSet>> {
         let mut constraints = HashMap::new();
-        let faces = [
-            Face::Left,
-            Face::Right,
-            Face::Front,
-            Face::Back,
-            Face::Down,
-            Face::Up,
-        ];
-        for face in faces {
+        let tile_count = self.connectors.len();
+        let twists = Vec::from_iter(
+            self.orientations
+                .iter()
+                .map(|orientation| Face::ALL.map(|face| orientation.twist(&face))),
+        );
+        for face in Face::ALL {
             let mut face_constraints = Vec::new();
-            let inverse_face = match face {
-                Face::Left => Face::Right,
-                Face::Right => Face::Left,
-                Face::Front => Face::Back,
-                Face::Back => Face::Front,
-                Face::Down => Face::Up,
-                Face::Up => Face::Down,
-            };
-            for (constraint_rotation, constraint_connectors) in
-                self.rotations.iter().zip(&self.connectors)
-            {
+            let inverse_face = face.opposite();
+            for (constraint_twists, constraint_connectors) in twists.iter().zip(&self.connectors) {
                 let constraint_connector = constraint_connectors.get(&face);
-                let mut valid_tiles = HashSet::new();
-                for (tile, (rotation, connectors)) in
-                    self.rotations.iter().zip(&self.connectors).enumerate()
-                {
+                let constraint_twist = &constraint_twists[face.index()];
+                let mut valid_tiles = BitSet::new(tile_count);
+                for (tile, (twists, connectors)) in twists.iter().zip(&self.connectors).enumerate() {
                     let connector = connectors.get(&inverse_face);
-                    let id_fits = constraint_connector.id == connector.id;
-                    let symmetry_fits = match constraint_connector.symmetry {
-                        Symmetry::Normal => {
-                            if face == Face::Down || face == Face::Up {
-                                connector.symmetry == Symmetry::Normal
-                                    && constraint_rotation == rotation
-                            } else {
-                                connector.symmetry == Symmetry::Inverse
-                            }
-                        }
-                        Symmetry::Inverse => connector.symmetry == Symmetry::Normal,
-                        Symmetry::Symmetrical => connector.symmetry == Symmetry::Symmetrical,
-                    };
-                    if id_fits && symmetry_fits {
+                    let twist = &twists[inverse_face.index()];
+                    if constraint_connector.fits(&face, constraint_twist, connector, twist) {
                         valid_tiles.insert(tile);
                     }
                 }
@@ -297,21 +780,303 @@ impl Tiles {
             }
             constraints.insert(face, face_constraints);
         }
-        let cs = constraints.get(&Face::Down).unwrap();
-        for (tile, valid_tiles) in cs.iter().enumerate() {
-            println!("---\n{:?}:", self.vox_paths[tile]);
-            for tile in valid_tiles.iter() {
-                println!("{:?}", self.vox_paths[*tile]);
+        return constraints;
+    }
+
+    /// Returns the problems with the tiles that can make `wfc` fail, found
+    /// without solving.
+    pub fn validate(&self) -> ValidationReport {
+        let constraints = self.constraints();
+        let tile_count = self.names.len();
+        let mut report = ValidationReport::default();
+        for face in Face::ALL {
+            let face_constraints = &constraints[&face];
+            let opposite_constraints = &constraints[&face.opposite()];
+            for (tile, valid_tiles) in face_constraints.iter().enumerate() {
+                if valid_tiles.is_empty() {
+                    report.dead_faces.push(DeadFace {
+                        tile: self.tile_name(tile),
+                        face: face.clone(),
+                    });
+                }
+                for neighbor in valid_tiles.iter() {
+                    if !opposite_constraints[neighbor].contains(tile) {
+                        report.asymmetric_adjacencies.push(AsymmetricAdjacency {
+                            tile: self.tile_name(tile),
+                            face: face.clone(),
+                            neighbor: self.tile_name(neighbor),
+                        });
+                    }
+                }
+            }
+
+            let opposite_ids = HashSet::<i64>::from_iter(
+                self.connectors.iter().map(|connectors| connectors.get(&face.opposite()).id),
+            );
+            let mut one_sided_tiles = BTreeMap::new();
+            for (tile, connectors) in self.connectors.iter().enumerate() {
+                let id = connectors.get(&face).id;
+                if !opposite_ids.contains(&id) {
+                    one_sided_tiles.entry(id).or_insert_with(Vec::new).push(self.tile_name(tile));
+                }
+            }
+            for (id, tiles) in one_sided_tiles {
+                report.one_sided_connectors.push(OneSidedConnector {
+                    id: id,
+                    face: face.clone(),
+                    tiles: tiles,
+                });
             }
         }
-        return constraints;
+
+        let mut is_reachable = vec![false; tile_count];
+        for face_constraints in constraints.values() {
+            for (tile, valid_tiles) in face_constraints.iter().enumerate() {
+                for neighbor in valid_tiles.iter().filter(|neighbor| *neighbor != tile) {
+                    is_reachable[neighbor] = true;
+                }
+            }
+        }
+        for (tile, is_reachable) in is_reachable.into_iter().enumerate() {
+            // A lone tile has no other tile to be placed next to
+            if !is_reachable && tile_count > 1 {
+                report.unreachable_tiles.push(self.tile_name(tile));
+            }
+        }
+        return report;
     }
 
-    pub fn size(&self) -> usize {
+    /// Returns the tiles matching `selector`, which is a tile name such as
+    /// `road@R90`, the name of the config tile a tile was generated from, or
+    /// one of its tags.
+    pub fn select(&self, selector: &str) -> BitSet {
+        let mut selected = BitSet::new(self.names.len());
+        for (tile, name) in self.names.iter().enumerate() {
+            let source = self.sources[tile];
+            let is_selected = name == selector
+                || self.names[source] == selector
+                || self.tags[source].iter().any(|tag| tag == selector);
+            if is_selected {
+                selected.insert(tile);
+            }
+        }
+        return selected;
+    }
+
+    /// Returns the tiles that fit a neighbor with a `Symmetrical` connector
+    /// `id` on `face`, by the same rule as `constraints`.
+    pub fn with_connector(&self, face: &Face, id: i64) -> BitSet {
+        let neighbor = Connector {
+            id: id,
+            symmetry: Symmetry::Symmetrical,
+        };
+        let mut selected = BitSet::new(self.names.len());
+        for (tile, (connectors, orientation)) in self.connectors.iter().zip(&self.orientations).enumerate() {
+            let twist = orientation.twist(face);
+            if connectors.get(face).fits(face, &twist, &neighbor, &twist) {
+                selected.insert(tile);
+            }
+        }
+        return selected;
+    }
+
+    /// Returns the name and source file of `tile` for reports.
+    fn tile_name(&self, tile: usize) -> TileName {
+        let source = self.sources[tile];
+        return TileName {
+            name: self.names[tile].clone(),
+            file: self.vox_paths[source].clone().or_else(|| self.tileset_path.clone()),
+        };
+    }
+
+    pub fn size(&self) -> [usize; 3] {
         return self.size;
     }
 
-    pub fn vox_paths(&self) -> &Vec<PathBuf> {
+    pub fn names(&self) -> &Vec<String> {
+        return &self.names;
+    }
+
+    pub fn voxes(&self) -> &Vec<Vox> {
+        return &self.voxes;
+    }
+
+    pub fn vox_paths(&self) -> &Vec<Option<PathBuf>> {
         return &self.vox_paths;
     }
+
+    pub fn rotations(&self) -> &Vec<Rotation> {
+        return &self.rotations;
+    }
+
+    pub fn orientations(&self) -> &Vec<Orientation> {
+        return &self.orientations;
+    }
+
+    /// Returns the relative frequency of each tile.
+    pub fn weights(&self) -> &Vec<f64> {
+        return &self.weights;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variant_weights() {
+        let mut tiles = Tiles::from("tests/samples/weights").unwrap();
+        tiles.generate_transformed_tiles();
+        let weights: HashMap<&str, f64> = tiles
+            .names()
+            .iter()
+            .map(|name| name.as_str())
+            .zip(tiles.weights().iter().copied())
+            .collect();
+        assert_eq!(weights["weights-0-empty"], 1000.0);
+        assert_eq!(weights["weights-2-stairs"], 0.001);
+        assert_eq!(weights["weights-2-stairs@R90"], 0.002);
+        assert_eq!(weights["weights-2-stairs@R180"], 0.001);
+    }
+
+    #[test]
+    fn test_symmetry() {
+        let mut tiles = Tiles::from("tests/samples/symmetry").unwrap();
+        tiles.generate_transformed_tiles();
+        let generated = Vec::from_iter(tiles.names().iter().skip(4).map(|name| name.as_str()));
+        assert_eq!(
+            generated,
+            [
+                "symmetry-1-path@R90",
+                "symmetry-1-path@R180",
+                "symmetry-1-path@R270",
+                "symmetry-1-path@fX",
+                "symmetry-1-path@fY",
+                "symmetry-1-path@R90fX",
+                "symmetry-1-path@R270fX",
+                "symmetry-2-stairs_0@R90",
+                "symmetry-2-stairs_0@R180",
+                "symmetry-2-stairs_0@R270",
+                "symmetry-3-stairs_1@R90",
+                "symmetry-3-stairs_1@R270fX",
+            ]
+        );
+        let backslash = serde_json::from_str::<TileSymmetry>(r#""\\""#).unwrap();
+        assert!(matches!(backslash, TileSymmetry::Class(SymmetryClass::Backslash)));
+    }
+    #[test]
+    fn test_orientations() {
+        let rotations = SymmetryGroup::Cube.transforms();
+        let mut orientations = HashSet::<Orientation>::from_iter(rotations.iter().map(|t| t.orientation()));
+        orientations.insert(Orientation::IDENTITY);
+        assert_eq!(orientations.len(), 24);
+        let transforms = SymmetryGroup::CubeReflections.transforms();
+        assert_eq!(transforms.len(), 47);
+        assert_eq!(Transform::from_name("RX90R180fX").unwrap().name(), "RX90R180fX");
+
+        let r90 = Orientation::from(&Rotation::R90);
+        assert_eq!(r90.twist(&Face::Up), Rotation::R90);
+        assert_eq!(r90.twist(&Face::Down), Rotation::R90);
+        assert_eq!(r90.twist(&Face::Left), Rotation::R0);
+        assert_eq!(r90.then(&r90.inverse()), Orientation::IDENTITY);
+    }
+
+    #[test]
+    fn test_cube_symmetry() {
+        let mut tiles = Tiles::from("tests/samples/cube").unwrap();
+        tiles.generate_transformed_tiles();
+        assert_eq!(tiles.names().len(), 24);
+        let connectors = HashSet::<&Connectors>::from_iter(tiles.connectors.iter());
+        assert_eq!(connectors.len(), 24);
+        let tipped = tiles.names().iter().position(|name| name == "cube-0-stairs@RX90").unwrap();
+        assert_eq!(tiles.connectors[tipped].up.id, 4);
+        assert_eq!(tiles.connectors[tipped].back.id, 5);
+        assert_eq!(tiles.voxes()[tipped].xyzis().len(), tiles.voxes()[0].xyzis().len());
+
+        // A column of tiles with asymmetric connectors still fits together on its side.
+        let mut tiles = Tiles::from("tests/samples/cube_asymmetric").unwrap();
+        tiles.generate_transformed_tiles();
+        let report = tiles.validate();
+        assert!(report.dead_faces.is_empty());
+        assert!(report.asymmetric_adjacencies.is_empty());
+        let constraints = tiles.constraints();
+        let position = |name: &str| tiles.names().iter().position(|tile_name| tile_name == name).unwrap();
+        let upright = position("cube_asymmetric-0-stairs");
+        assert!(constraints[&Face::Up][upright].contains(upright));
+        assert!(constraints[&Face::Right][upright].contains(upright));
+        let tipped = position("cube_asymmetric-0-stairs@RX90");
+        assert!(constraints[&Face::Back][tipped].contains(tipped));
+        assert!(constraints[&Face::Front][tipped].contains(tipped));
+        let upside_down = position("cube_asymmetric-0-stairs@RX180");
+        assert!(constraints[&Face::Up][upside_down].contains(upside_down));
+        assert!(constraints[&Face::Down][upside_down].contains(upside_down));
+        let turned = position("cube_asymmetric-0-stairs@R90");
+        assert!(!constraints[&Face::Up][upright].contains(turned));
+        assert!(constraints[&Face::Down][upside_down].contains(upright));
+        assert!(constraints[&Face::Up][upright].contains(upside_down));
+    }
+
+    #[test]
+    fn test_tile_size() {
+        let mut tiles = Tiles::from("tests/samples/slab").unwrap();
+        assert_eq!(tiles.size(), [16, 16, 8]);
+        tiles.generate_transformed_tiles();
+        let generated = Vec::from_iter(tiles.names().iter().skip(3).map(|name| name.as_str()));
+        assert!(generated.contains(&"slab-2-ramp@RX180R90"));
+        let sideways = ["RX90", "RX270", "RY90", "RY270"];
+        assert!(!generated.iter().any(|name| sideways.iter().any(|tilt| name.contains(tilt))));
+        assert!(tiles.voxes().iter().all(|vox| vox.sizes() == vec![[16, 16, 8]]));
+        let size = serde_json::from_str::<TileSize>("16").unwrap();
+        assert_eq!(size.xyz(), [16, 16, 16]);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut tiles = Tiles::from("tests/samples/asymmetric").unwrap();
+        tiles.generate_transformed_tiles();
+        let report = tiles.validate();
+        // The base's vertical Inverse connector and the top's Normal one fit both ways.
+        assert!(report.dead_faces.is_empty());
+        assert!(report.asymmetric_adjacencies.is_empty());
+        assert!(report.one_sided_connectors.is_empty());
+        let tileset_path = PathBuf::from("tests/samples/asymmetric/../tileset/tileset.vox");
+        assert_eq!(report.unreachable_tiles[0].name, "asymmetric-2-island");
+        assert_eq!(report.unreachable_tiles[0].file, Some(tileset_path));
+
+        let mut tiles = Tiles::from("tests/samples/unsatisfiable").unwrap();
+        tiles.generate_transformed_tiles();
+        let report = tiles.validate();
+        let one_sided = Vec::from_iter(report.one_sided_connectors.iter().map(|c| (c.id, &c.face)));
+        assert_eq!(one_sided, [(1, &Face::Down), (2, &Face::Up)]);
+        assert_eq!(report.dead_faces.len(), 2);
+        assert!(report.unreachable_tiles.is_empty());
+    }
+
+    #[test]
+    fn test_select() {
+        let mut tiles = Tiles::from("tests/samples/tileset").unwrap();
+        tiles.generate_transformed_tiles();
+        let names = |selected: BitSet| Vec::from_iter(selected.iter().map(|tile| tiles.names()[tile].clone()));
+        assert_eq!(names(tiles.select("tileset-1-path")), ["tileset-1-path"]);
+        assert_eq!(names(tiles.select("tileset-2-stairs_0@R90")), ["tileset-2-stairs_0@R90"]);
+        assert_eq!(tiles.select("tileset-2-stairs_0").iter().count(), 4);
+        assert_eq!(tiles.select("stairs").iter().count(), 8);
+        assert!(tiles.select("bridges").is_empty());
+    }
+
+    #[test]
+    fn test_with_connector() {
+        let mut tiles = Tiles::from("tests/samples/tileset").unwrap();
+        tiles.generate_transformed_tiles();
+        let names = |selected: BitSet| Vec::from_iter(selected.iter().map(|tile| tiles.names()[tile].clone()));
+        assert_eq!(names(tiles.with_connector(&Face::Down, 99)).len(), 5);
+        assert!(names(tiles.with_connector(&Face::Down, 99)).contains(&"tileset-1-path".to_string()));
+
+        // An asymmetric connector cannot fit the symmetrical connector of a boundary.
+        let mut tiles = Tiles::from("tests/samples/concrete_2").unwrap();
+        tiles.generate_transformed_tiles();
+        let edge = tiles.names().iter().position(|name| name == "config-4-path_edge").unwrap();
+        assert_eq!(tiles.connectors[edge].up.id, 21);
+        assert!(!tiles.with_connector(&Face::Up, 21).contains(edge));
+    }
 }
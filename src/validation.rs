@@ -0,0 +1,96 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use super::model::Face;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A tile named in a `ValidationReport`.
+pub struct TileName {
+    pub name: String,
+    /// The VOX file the tile or the tile it was generated from was loaded from.
+    pub file: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A face of a tile that no tile can be placed against.
+pub struct DeadFace {
+    pub tile: TileName,
+    pub face: Face,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A connector id found on `face` of `tiles` but on no tile's opposite face.
+pub struct OneSidedConnector {
+    pub id: i64,
+    pub face: Face,
+    pub tiles: Vec<TileName>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A tile that allows `neighbor` on `face` without `neighbor` allowing it on
+/// the opposite face.
+pub struct AsymmetricAdjacency {
+    pub tile: TileName,
+    pub face: Face,
+    pub neighbor: TileName,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Problems found in a set of tiles before solving, which can make every
+/// attempt end in a contradiction.
+pub struct ValidationReport {
+    pub dead_faces: Vec<DeadFace>,
+    pub one_sided_connectors: Vec<OneSidedConnector>,
+    pub asymmetric_adjacencies: Vec<AsymmetricAdjacency>,
+    /// Tiles that no other tile allows on any face.
+    pub unreachable_tiles: Vec<TileName>,
+}
+
+impl ValidationReport {
+    /// Returns true if no problem was found.
+    pub fn is_empty(&self) -> bool {
+        return self.dead_faces.is_empty()
+            && self.one_sided_connectors.is_empty()
+            && self.asymmetric_adjacencies.is_empty()
+            && self.unreachable_tiles.is_empty();
+    }
+}
+
+impl fmt::Display for TileName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match &self.file {
+            Some(file) => write!(f, "{} ({})", self.name, file.display()),
+            None => write!(f, "{}", self.name),
+        };
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    /// Writes one line per problem.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for dead_face in self.dead_faces.iter() {
+            writeln!(f, "tile {} has no valid neighbor on face {:?}", dead_face.tile, dead_face.face)?;
+        }
+        for connector in self.one_sided_connectors.iter() {
+            let tiles = Vec::from_iter(connector.tiles.iter().map(|tile| tile.to_string()));
+            writeln!(
+                f,
+                "connector {} is only found on face {:?}, of tiles {}",
+                connector.id,
+                connector.face,
+                tiles.join(", ")
+            )?;
+        }
+        for adjacency in self.asymmetric_adjacencies.iter() {
+            writeln!(
+                f,
+                "tile {} allows {} on face {:?} but not the reverse",
+                adjacency.tile, adjacency.neighbor, adjacency.face
+            )?;
+        }
+        for tile in self.unreachable_tiles.iter() {
+            writeln!(f, "tile {} is not allowed next to any other tile", tile)?;
+        }
+        return Ok(());
+    }
+}
@@ -1,18 +1,21 @@
 use std::fs::File;
-use std::io::BufRead;
+use std::io;
 use std::io::{BufReader, BufWriter};
 use std::io::{Read, Write};
 use std::path::Path;
 
-use super::tile::{Rotation,Axis};
+use super::error::{Error, Result};
+use super::tile::{Axis, Orientation, Rotation};
 
 const TAG_SIZE: usize = 4;
 const VOX_TAG: &[u8; TAG_SIZE] = b"VOX ";
 const INT_SIZE: usize = 4;
-const VERSION: i32 = 150;
+/// The oldest version read, whose chunk layout later versions extend with
+/// chunks kept as they are.
+const MIN_VERSION: i32 = 150;
 
 const MAIN_TAG: &[u8; TAG_SIZE] = b"MAIN";
-const CHUNK_BYTE_COUNTS_SIZE: usize = 8;
+const PACK_TAG: &[u8; TAG_SIZE] = b"PACK";
 
 const SIZE_TAG: &[u8; TAG_SIZE] = b"SIZE";
 
@@ -23,243 +26,911 @@ const RGBA_TAG: &[u8; TAG_SIZE] = b"RGBA";
 const PALETTE_RGBA_COUNT: usize = 256;
 const RGBA_SIZE: usize = 4;
 
-// TODO: Propogate these errors up the stack
-fn read_tag(reader: &mut BufReader<File>) -> [u8; TAG_SIZE] {
+const TRANSFORM_TAG: &[u8; TAG_SIZE] = b"nTRN";
+const GROUP_TAG: &[u8; TAG_SIZE] = b"nGRP";
+const SHAPE_TAG: &[u8; TAG_SIZE] = b"nSHP";
+const LAYER_TAG: &[u8; TAG_SIZE] = b"LAYR";
+const MATERIAL_TAG: &[u8; TAG_SIZE] = b"MATL";
+const RENDER_OBJECT_TAG: &[u8; TAG_SIZE] = b"rOBJ";
+
+/// The maximum size of a model on each axis.
+const MAX_MODEL_SIZE: usize = 256;
+
+/// The palette used by files without an RGBA chunk, indexed by color index
+/// with each color packed as `0xAABBGGRR`.
+const DEFAULT_PALETTE: [u32; PALETTE_RGBA_COUNT] = [
+    0x00000000, 0xffffffff, 0xffccffff, 0xff99ffff, 0xff66ffff, 0xff33ffff, 0xff00ffff, 0xffffccff,
+    0xffccccff, 0xff99ccff, 0xff66ccff, 0xff33ccff, 0xff00ccff, 0xffff99ff, 0xffcc99ff, 0xff9999ff,
+    0xff6699ff, 0xff3399ff, 0xff0099ff, 0xffff66ff, 0xffcc66ff, 0xff9966ff, 0xff6666ff, 0xff3366ff,
+    0xff0066ff, 0xffff33ff, 0xffcc33ff, 0xff9933ff, 0xff6633ff, 0xff3333ff, 0xff0033ff, 0xffff00ff,
+    0xffcc00ff, 0xff9900ff, 0xff6600ff, 0xff3300ff, 0xff0000ff, 0xffffffcc, 0xffccffcc, 0xff99ffcc,
+    0xff66ffcc, 0xff33ffcc, 0xff00ffcc, 0xffffcccc, 0xffcccccc, 0xff99cccc, 0xff66cccc, 0xff33cccc,
+    0xff00cccc, 0xffff99cc, 0xffcc99cc, 0xff9999cc, 0xff6699cc, 0xff3399cc, 0xff0099cc, 0xffff66cc,
+    0xffcc66cc, 0xff9966cc, 0xff6666cc, 0xff3366cc, 0xff0066cc, 0xffff33cc, 0xffcc33cc, 0xff9933cc,
+    0xff6633cc, 0xff3333cc, 0xff0033cc, 0xffff00cc, 0xffcc00cc, 0xff9900cc, 0xff6600cc, 0xff3300cc,
+    0xff0000cc, 0xffffff99, 0xffccff99, 0xff99ff99, 0xff66ff99, 0xff33ff99, 0xff00ff99, 0xffffcc99,
+    0xffcccc99, 0xff99cc99, 0xff66cc99, 0xff33cc99, 0xff00cc99, 0xffff9999, 0xffcc9999, 0xff999999,
+    0xff669999, 0xff339999, 0xff009999, 0xffff6699, 0xffcc6699, 0xff996699, 0xff666699, 0xff336699,
+    0xff006699, 0xffff3399, 0xffcc3399, 0xff993399, 0xff663399, 0xff333399, 0xff003399, 0xffff0099,
+    0xffcc0099, 0xff990099, 0xff660099, 0xff330099, 0xff000099, 0xffffff66, 0xffccff66, 0xff99ff66,
+    0xff66ff66, 0xff33ff66, 0xff00ff66, 0xffffcc66, 0xffcccc66, 0xff99cc66, 0xff66cc66, 0xff33cc66,
+    0xff00cc66, 0xffff9966, 0xffcc9966, 0xff999966, 0xff669966, 0xff339966, 0xff009966, 0xffff6666,
+    0xffcc6666, 0xff996666, 0xff666666, 0xff336666, 0xff006666, 0xffff3366, 0xffcc3366, 0xff993366,
+    0xff663366, 0xff333366, 0xff003366, 0xffff0066, 0xffcc0066, 0xff990066, 0xff660066, 0xff330066,
+    0xff000066, 0xffffff33, 0xffccff33, 0xff99ff33, 0xff66ff33, 0xff33ff33, 0xff00ff33, 0xffffcc33,
+    0xffcccc33, 0xff99cc33, 0xff66cc33, 0xff33cc33, 0xff00cc33, 0xffff9933, 0xffcc9933, 0xff999933,
+    0xff669933, 0xff339933, 0xff009933, 0xffff6633, 0xffcc6633, 0xff996633, 0xff666633, 0xff336633,
+    0xff006633, 0xffff3333, 0xffcc3333, 0xff993333, 0xff663333, 0xff333333, 0xff003333, 0xffff0033,
+    0xffcc0033, 0xff990033, 0xff660033, 0xff330033, 0xff000033, 0xffffff00, 0xffccff00, 0xff99ff00,
+    0xff66ff00, 0xff33ff00, 0xff00ff00, 0xffffcc00, 0xffcccc00, 0xff99cc00, 0xff66cc00, 0xff33cc00,
+    0xff00cc00, 0xffff9900, 0xffcc9900, 0xff999900, 0xff669900, 0xff339900, 0xff009900, 0xffff6600,
+    0xffcc6600, 0xff996600, 0xff666600, 0xff336600, 0xff006600, 0xffff3300, 0xffcc3300, 0xff993300,
+    0xff663300, 0xff333300, 0xff003300, 0xffff0000, 0xffcc0000, 0xff990000, 0xff660000, 0xff330000,
+    0xffee0000, 0xffdd0000, 0xffbb0000, 0xffaa0000, 0xff880000, 0xff770000, 0xff550000, 0xff440000,
+    0xff220000, 0xff110000, 0xff00ee00, 0xff00dd00, 0xff00bb00, 0xff00aa00, 0xff008800, 0xff007700,
+    0xff005500, 0xff004400, 0xff002200, 0xff001100, 0xff0000ee, 0xff0000dd, 0xff0000bb, 0xff0000aa,
+    0xff000088, 0xff000077, 0xff000055, 0xff000044, 0xff000022, 0xff000011, 0xffeeeeee, 0xffdddddd,
+    0xffbbbbbb, 0xffaaaaaa, 0xff888888, 0xff777777, 0xff555555, 0xff444444, 0xff222222, 0xff111111,
+];
+
+/// A reader that counts the bytes read so errors can report their offset.
+struct OffsetReader<R> {
+    reader: R,
+    offset: u64,
+}
+
+impl<R: Read> OffsetReader<R> {
+    fn new(reader: R) -> Self {
+        return Self {
+            reader: reader,
+            offset: 0,
+        };
+    }
+
+    /// Returns the number of bytes read so far.
+    fn offset(&self) -> u64 {
+        return self.offset;
+    }
+
+    /// Reads and discards `size` bytes.
+    fn skip(&mut self, size: u64) -> Result<()> {
+        let skipped = io::copy(&mut self.by_ref().take(size), &mut io::sink())?;
+        if skipped < size {
+            return Err(Error::Io(io::ErrorKind::UnexpectedEof.into()));
+        }
+        return Ok(());
+    }
+}
+
+impl<R: Read> Read for OffsetReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let size = self.reader.read(buf)?;
+        self.offset += size as u64;
+        return Ok(size);
+    }
+}
+
+fn read_tag<R: Read>(reader: &mut R) -> Result<[u8; TAG_SIZE]> {
     let mut tag = [0; TAG_SIZE];
-    reader.read(&mut tag).unwrap();
-    return tag;
+    reader.read_exact(&mut tag)?;
+    return Ok(tag);
 }
 
-fn read_int(reader: &mut BufReader<File>) -> i32 {
+/// Reads a tag and checks it is `expected`.
+fn expect_tag<R: Read>(reader: &mut OffsetReader<R>, expected: &[u8; TAG_SIZE]) -> Result<()> {
+    let offset = reader.offset();
+    let tag = read_tag(reader)?;
+    if tag != *expected {
+        return Err(Error::MalformedVox {
+            offset: offset,
+            message: format!(
+                "expected {:?} tag, found {:?}",
+                String::from_utf8_lossy(expected),
+                String::from_utf8_lossy(&tag)
+            ),
+        });
+    }
+    return Ok(());
+}
+
+fn read_int<R: Read>(reader: &mut R) -> Result<i32> {
     let mut int_bytes = [0; INT_SIZE];
-    reader.read(&mut int_bytes).unwrap();
+    reader.read_exact(&mut int_bytes)?;
     let i = i32::from_le_bytes(int_bytes);
-    return i;
+    return Ok(i);
 }
 
-fn read_xyzi(reader: &mut BufReader<File>) -> [u8; XYZI_SIZE] {
+/// Reads an int that counts bytes or items, which must not be negative.
+fn read_count<R: Read>(reader: &mut OffsetReader<R>, name: &str) -> Result<usize> {
+    let offset = reader.offset();
+    let count = read_int(reader)?;
+    if count < 0 {
+        return Err(Error::MalformedVox {
+            offset: offset,
+            message: format!("negative {} {}", name, count),
+        });
+    }
+    return Ok(count as usize);
+}
+
+fn read_bytes<R: Read>(reader: &mut R, size: usize) -> Result<Vec<u8>> {
+    // Sizes come from the file, so the buffer only grows as bytes arrive
+    let mut bytes = Vec::new();
+    reader.take(size as u64).read_to_end(&mut bytes)?;
+    if bytes.len() < size {
+        return Err(Error::Io(io::ErrorKind::UnexpectedEof.into()));
+    }
+    return Ok(bytes);
+}
+
+fn read_string<R: Read>(reader: &mut OffsetReader<R>) -> Result<String> {
+    let size = read_count(reader, "string size")?;
+    let offset = reader.offset();
+    let bytes = read_bytes(reader, size)?;
+    return String::from_utf8(bytes).map_err(|_| Error::MalformedVox {
+        offset: offset,
+        message: "string is not valid utf-8".to_string(),
+    });
+}
+
+fn read_dict<R: Read>(reader: &mut OffsetReader<R>) -> Result<Dict> {
+    let pair_count = read_count(reader, "dict size")?;
+    let mut dict = Vec::new();
+    for _ in 0..pair_count {
+        let key = read_string(reader)?;
+        let value = read_string(reader)?;
+        dict.push((key, value));
+    }
+    return Ok(dict);
+}
+
+fn read_xyzi<R: Read>(reader: &mut R) -> Result<[u8; XYZI_SIZE]> {
     let mut xyzi = [0; XYZI_SIZE];
-    reader.read(&mut xyzi).unwrap();
-    return xyzi;
+    reader.read_exact(&mut xyzi)?;
+    return Ok(xyzi);
 }
 
-fn read_rgba(reader: &mut BufReader<File>) -> [u8; RGBA_SIZE] {
+fn read_rgba<R: Read>(reader: &mut R) -> Result<[u8; RGBA_SIZE]> {
     let mut rgba = [0; RGBA_SIZE];
-    reader.read(&mut rgba).unwrap();
-    return rgba;
+    reader.read_exact(&mut rgba)?;
+    return Ok(rgba);
 }
 
-fn write_tag(tag: &[u8; TAG_SIZE], writer: &mut BufWriter<File>) {
-    writer.write(tag).unwrap();
+fn write_tag<W: Write>(tag: &[u8; TAG_SIZE], writer: &mut W) -> Result<()> {
+    writer.write_all(tag)?;
+    return Ok(());
 }
 
-fn write_int(i: i32, writer: &mut BufWriter<File>) {
+fn write_int<W: Write>(i: i32, writer: &mut W) -> Result<()> {
     let int_bytes = i32::to_le_bytes(i);
-    writer.write(&int_bytes).unwrap();
+    writer.write_all(&int_bytes)?;
+    return Ok(());
+}
+
+fn write_string<W: Write>(string: &str, writer: &mut W) -> Result<()> {
+    write_int(string.len() as i32, writer)?;
+    writer.write_all(string.as_bytes())?;
+    return Ok(());
 }
 
-fn write_xyzi(xyzi: &[u8; XYZI_SIZE], writer: &mut BufWriter<File>) {
-    writer.write(xyzi).unwrap();
+fn write_dict<W: Write>(dict: &Dict, writer: &mut W) -> Result<()> {
+    write_int(dict.len() as i32, writer)?;
+    for (key, value) in dict.iter() {
+        write_string(key, writer)?;
+        write_string(value, writer)?;
+    }
+    return Ok(());
 }
 
-fn write_rgba(rgba: &[u8; RGBA_SIZE], writer: &mut BufWriter<File>) {
-    writer.write(rgba).unwrap();
+fn write_xyzi<W: Write>(xyzi: &[u8; XYZI_SIZE], writer: &mut W) -> Result<()> {
+    writer.write_all(xyzi)?;
+    return Ok(());
 }
 
-/// A MagicaVoxel object
-pub struct Vox {
-    version: i32,
+fn write_rgba<W: Write>(rgba: &[u8; RGBA_SIZE], writer: &mut W) -> Result<()> {
+    writer.write_all(rgba)?;
+    return Ok(());
+}
+
+/// Writes a chunk with its content and children byte counts.
+fn write_chunk<W: Write>(
+    tag: &[u8; TAG_SIZE],
+    content: &[u8],
+    children: &[u8],
+    writer: &mut W,
+) -> Result<()> {
+    write_tag(tag, writer)?;
+    write_int(content.len() as i32, writer)?;
+    write_int(children.len() as i32, writer)?;
+    writer.write_all(content)?;
+    writer.write_all(children)?;
+    return Ok(());
+}
+
+/// The RGBA colors of a VOX file indexed by color index.
+pub type Palette = [[u8; RGBA_SIZE]; PALETTE_RGBA_COUNT];
+
+/// The key and value pairs of a VOX dictionary in file order.
+pub type Dict = Vec<(String, String)>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The voxels of a SIZE and XYZI chunk pair.
+pub struct VoxModel {
     x_size: i32,
     y_size: i32,
     z_size: i32,
-    voxel_count: i32,
     xyzis: Vec<[u8; XYZI_SIZE]>,
-    palette: [[u8; RGBA_SIZE]; PALETTE_RGBA_COUNT],
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A node of the scene graph that places models in the world.
+pub enum Node {
+    /// An nTRN chunk, with the `_r` and `_t` attributes of each animation frame.
+    Transform {
+        id: i32,
+        attributes: Dict,
+        child: i32,
+        reserved: i32,
+        layer: i32,
+        frames: Vec<Dict>,
+    },
+    /// An nGRP chunk.
+    Group {
+        id: i32,
+        attributes: Dict,
+        children: Vec<i32>,
+    },
+    /// An nSHP chunk, with the attributes of each of its models.
+    Shape {
+        id: i32,
+        attributes: Dict,
+        models: Vec<(i32, Dict)>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A LAYR chunk.
+pub struct Layer {
+    id: i32,
+    attributes: Dict,
+    reserved: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A MATL chunk with the material properties of a palette color.
+pub struct Material {
+    id: i32,
+    properties: Dict,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A chunk this module does not interpret, kept so it can be written back.
+pub struct RawChunk {
+    tag: [u8; TAG_SIZE],
+    content: Vec<u8>,
+    children: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A MagicaVoxel object
+///
+/// Chunks are written back in the order MagicaVoxel writes them: models,
+/// scene graph nodes, layers, the palette, materials, render objects and
+/// finally any chunks that were not interpreted.
+pub struct Vox {
+    version: i32,
+    models: Vec<VoxModel>,
+    nodes: Vec<Node>,
+    layers: Vec<Layer>,
+    palette: Palette,
+    materials: Vec<Material>,
+    render_objects: Vec<Dict>,
+    other_chunks: Vec<RawChunk>,
 }
 
 impl Vox {
     /// Read object data from a file
-    pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
         let file = File::open(path)?;
-        let mut reader = BufReader::new(file);
+        return Self::read(BufReader::new(file));
+    }
+
+    /// Read object data from the bytes of a VOX file.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        return Self::read(bytes);
+    }
+
+    /// Read object data from `reader`, which is read to the end of the MAIN chunk.
+    pub fn read<R: Read>(reader: R) -> Result<Self> {
+        let mut reader = OffsetReader::new(reader);
 
-        let vox_tag = read_tag(&mut reader);
-        if vox_tag != *VOX_TAG {
-            panic!("Unsupported file format");
+        expect_tag(&mut reader, VOX_TAG)?;
+        let version = read_int(&mut reader)?;
+        if version < MIN_VERSION {
+            return Err(Error::MalformedVox {
+                offset: TAG_SIZE as u64,
+                message: format!("unsupported version {}, expected {} or later", version, MIN_VERSION),
+            });
+        }
+
+        expect_tag(&mut reader, MAIN_TAG)?;
+        let main_content_size = read_count(&mut reader, "chunk content size")?;
+        let main_children_size = read_count(&mut reader, "chunk children size")?;
+        reader.skip(main_content_size as u64)?;
+        let main_end = reader.offset() + main_children_size as u64;
+
+        let mut vox = Self {
+            version: version,
+            models: Vec::new(),
+            nodes: Vec::new(),
+            layers: Vec::new(),
+            palette: DEFAULT_PALETTE.map(u32::to_le_bytes),
+            materials: Vec::new(),
+            render_objects: Vec::new(),
+            other_chunks: Vec::new(),
+        };
+        let mut size = None;
+        while reader.offset() < main_end {
+            let chunk_offset = reader.offset();
+            let tag = read_tag(&mut reader)?;
+            let content_size = read_count(&mut reader, "chunk content size")?;
+            let children_size = read_count(&mut reader, "chunk children size")?;
+            let content_end = reader.offset() + content_size as u64;
+            match &tag {
+                SIZE_TAG => {
+                    let x_size = read_int(&mut reader)?;
+                    let y_size = read_int(&mut reader)?;
+                    let z_size = read_int(&mut reader)?;
+                    let is_valid_size = |axis_size| (1..=MAX_MODEL_SIZE as i32).contains(&axis_size);
+                    if ![x_size, y_size, z_size].into_iter().all(is_valid_size) {
+                        return Err(Error::MalformedVox {
+                            offset: chunk_offset,
+                            message: format!("invalid model size {:?}", (x_size, y_size, z_size)),
+                        });
+                    }
+                    size = Some((x_size, y_size, z_size));
+                }
+                XYZI_TAG => {
+                    let (x_size, y_size, z_size) = size.take().ok_or(Error::MalformedVox {
+                        offset: chunk_offset,
+                        message: "XYZI chunk without a preceding SIZE chunk".to_string(),
+                    })?;
+                    let voxel_count = read_count(&mut reader, "voxel count")?;
+                    let mut xyzis = Vec::new();
+                    for _ in 0..voxel_count {
+                        let xyzi_offset = reader.offset();
+                        let xyzi = read_xyzi(&mut reader)?;
+                        let is_inside = (xyzi[0] as i32) < x_size
+                            && (xyzi[1] as i32) < y_size
+                            && (xyzi[2] as i32) < z_size;
+                        if !is_inside {
+                            return Err(Error::MalformedVox {
+                                offset: xyzi_offset,
+                                message: format!("voxel {:?} outside the model size", &xyzi[..3]),
+                            });
+                        }
+                        xyzis.push(xyzi);
+                    }
+                    vox.models.push(VoxModel {
+                        x_size: x_size,
+                        y_size: y_size,
+                        z_size: z_size,
+                        xyzis: xyzis,
+                    });
+                }
+                RGBA_TAG => {
+                    // Entry i of the chunk is color index i + 1, so the last
+                    // entry is kept in the unused color index 0
+                    for i in 0..PALETTE_RGBA_COUNT {
+                        vox.palette[(i + 1) % PALETTE_RGBA_COUNT] = read_rgba(&mut reader)?;
+                    }
+                }
+                PACK_TAG => (), // The model count is implied by the SIZE and XYZI chunks
+                TRANSFORM_TAG => {
+                    let id = read_int(&mut reader)?;
+                    let attributes = read_dict(&mut reader)?;
+                    let child = read_int(&mut reader)?;
+                    let reserved = read_int(&mut reader)?;
+                    let layer = read_int(&mut reader)?;
+                    let frame_count = read_count(&mut reader, "frame count")?;
+                    let mut frames = Vec::new();
+                    for _ in 0..frame_count {
+                        frames.push(read_dict(&mut reader)?);
+                    }
+                    vox.nodes.push(Node::Transform {
+                        id: id,
+                        attributes: attributes,
+                        child: child,
+                        reserved: reserved,
+                        layer: layer,
+                        frames: frames,
+                    });
+                }
+                GROUP_TAG => {
+                    let id = read_int(&mut reader)?;
+                    let attributes = read_dict(&mut reader)?;
+                    let child_count = read_count(&mut reader, "child count")?;
+                    let mut children = Vec::new();
+                    for _ in 0..child_count {
+                        children.push(read_int(&mut reader)?);
+                    }
+                    vox.nodes.push(Node::Group {
+                        id: id,
+                        attributes: attributes,
+                        children: children,
+                    });
+                }
+                SHAPE_TAG => {
+                    let id = read_int(&mut reader)?;
+                    let attributes = read_dict(&mut reader)?;
+                    let model_count = read_count(&mut reader, "model count")?;
+                    let mut models = Vec::new();
+                    for _ in 0..model_count {
+                        let model = read_int(&mut reader)?;
+                        models.push((model, read_dict(&mut reader)?));
+                    }
+                    vox.nodes.push(Node::Shape {
+                        id: id,
+                        attributes: attributes,
+                        models: models,
+                    });
+                }
+                LAYER_TAG => {
+                    let id = read_int(&mut reader)?;
+                    let attributes = read_dict(&mut reader)?;
+                    let reserved = read_int(&mut reader)?;
+                    vox.layers.push(Layer {
+                        id: id,
+                        attributes: attributes,
+                        reserved: reserved,
+                    });
+                }
+                MATERIAL_TAG => {
+                    let id = read_int(&mut reader)?;
+                    let properties = read_dict(&mut reader)?;
+                    vox.materials.push(Material {
+                        id: id,
+                        properties: properties,
+                    });
+                }
+                RENDER_OBJECT_TAG => {
+                    vox.render_objects.push(read_dict(&mut reader)?);
+                }
+                _ => {
+                    let content = read_bytes(&mut reader, content_size)?;
+                    let children = read_bytes(&mut reader, children_size)?;
+                    vox.other_chunks.push(RawChunk {
+                        tag: tag,
+                        content: content,
+                        children: children,
+                    });
+                    continue;
+                }
+            }
+            if reader.offset() > content_end {
+                return Err(Error::MalformedVox {
+                    offset: chunk_offset,
+                    message: format!(
+                        "{:?} chunk content is larger than its size {}",
+                        String::from_utf8_lossy(&tag),
+                        content_size
+                    ),
+                });
+            }
+            reader.skip(content_end - reader.offset() + children_size as u64)?;
         }
-        let version = read_int(&mut reader);
-        if version != VERSION {
-            panic!("Unsupported version, expected {:?}", VERSION);
+        if vox.models.is_empty() {
+            return Err(Error::MalformedVox {
+                offset: main_end,
+                message: "no SIZE and XYZI chunks".to_string(),
+            });
         }
+        return Ok(vox);
+    }
+
+    /// Write object data to a file
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let file = File::create(path)?;
+        return self.write_to(BufWriter::new(file));
+    }
 
-        let main_tag = read_tag(&mut reader);
-        if main_tag != *MAIN_TAG {
-            panic!("Bad file format, expected {:?} tag", main_tag);
+    /// Write object data to `writer`.
+    pub fn write_to<W: Write>(&self, writer: W) -> Result<()> {
+        let mut writer = writer;
+        let mut children = Vec::new();
+        for model in self.models.iter() {
+            let mut size = Vec::new();
+            write_int(model.x_size, &mut size)?;
+            write_int(model.y_size, &mut size)?;
+            write_int(model.z_size, &mut size)?;
+            write_chunk(SIZE_TAG, &size, &[], &mut children)?;
+
+            let mut xyzi = Vec::new();
+            write_int(model.xyzis.len() as i32, &mut xyzi)?;
+            for model_xyzi in model.xyzis.iter() {
+                write_xyzi(model_xyzi, &mut xyzi)?;
+            }
+            write_chunk(XYZI_TAG, &xyzi, &[], &mut children)?;
         }
-        reader.consume(CHUNK_BYTE_COUNTS_SIZE);
 
-        let size_tag = read_tag(&mut reader);
-        if size_tag != *SIZE_TAG {
-            panic!("Bad file format, expected {:?} tag", size_tag);
+        for node in self.nodes.iter() {
+            let mut content = Vec::new();
+            let tag = match node {
+                Node::Transform { id, attributes, child, reserved, layer, frames } => {
+                    write_int(*id, &mut content)?;
+                    write_dict(attributes, &mut content)?;
+                    write_int(*child, &mut content)?;
+                    write_int(*reserved, &mut content)?;
+                    write_int(*layer, &mut content)?;
+                    write_int(frames.len() as i32, &mut content)?;
+                    for frame in frames.iter() {
+                        write_dict(frame, &mut content)?;
+                    }
+                    TRANSFORM_TAG
+                }
+                Node::Group { id, attributes, children } => {
+                    write_int(*id, &mut content)?;
+                    write_dict(attributes, &mut content)?;
+                    write_int(children.len() as i32, &mut content)?;
+                    for child in children.iter() {
+                        write_int(*child, &mut content)?;
+                    }
+                    GROUP_TAG
+                }
+                Node::Shape { id, attributes, models } => {
+                    write_int(*id, &mut content)?;
+                    write_dict(attributes, &mut content)?;
+                    write_int(models.len() as i32, &mut content)?;
+                    for (model, model_attributes) in models.iter() {
+                        write_int(*model, &mut content)?;
+                        write_dict(model_attributes, &mut content)?;
+                    }
+                    SHAPE_TAG
+                }
+            };
+            write_chunk(tag, &content, &[], &mut children)?;
         }
-        reader.consume(CHUNK_BYTE_COUNTS_SIZE);
-        let x_size = read_int(&mut reader);
-        let y_size = read_int(&mut reader);
-        let z_size = read_int(&mut reader);
-
-        let xyzi_tag = read_tag(&mut reader);
-        if xyzi_tag != *XYZI_TAG {
-            panic!("Bad file format, expected {:?} tag", xyzi_tag);
+
+        for layer in self.layers.iter() {
+            let mut content = Vec::new();
+            write_int(layer.id, &mut content)?;
+            write_dict(&layer.attributes, &mut content)?;
+            write_int(layer.reserved, &mut content)?;
+            write_chunk(LAYER_TAG, &content, &[], &mut children)?;
+        }
+
+        let mut rgba = Vec::new();
+        for i in 0..PALETTE_RGBA_COUNT {
+            write_rgba(&self.palette[(i + 1) % PALETTE_RGBA_COUNT], &mut rgba)?;
         }
-        reader.consume(CHUNK_BYTE_COUNTS_SIZE);
-        let voxel_count = read_int(&mut reader);
-        let mut xyzis = vec![[0; 4]; voxel_count as usize];
-        for xyzi in xyzis.iter_mut() {
-            *xyzi = read_xyzi(&mut reader);
+        write_chunk(RGBA_TAG, &rgba, &[], &mut children)?;
+
+        for material in self.materials.iter() {
+            let mut content = Vec::new();
+            write_int(material.id, &mut content)?;
+            write_dict(&material.properties, &mut content)?;
+            write_chunk(MATERIAL_TAG, &content, &[], &mut children)?;
         }
 
-        let rgba_tag = read_tag(&mut reader);
-        if rgba_tag != *RGBA_TAG {
-            panic!("Bad file format, expected {:?} tag", rgba_tag);
+        for render_object in self.render_objects.iter() {
+            let mut content = Vec::new();
+            write_dict(render_object, &mut content)?;
+            write_chunk(RENDER_OBJECT_TAG, &content, &[], &mut children)?;
         }
-        reader.consume(CHUNK_BYTE_COUNTS_SIZE);
-        let mut palette = [[0; 4]; PALETTE_RGBA_COUNT];
-        for i in 1..PALETTE_RGBA_COUNT {
-            palette[i] = read_rgba(&mut reader);
+
+        for chunk in self.other_chunks.iter() {
+            write_chunk(&chunk.tag, &chunk.content, &chunk.children, &mut children)?;
         }
-        let vox = Self {
-            version: version,
-            x_size: x_size,
-            y_size: y_size,
-            z_size: z_size,
-            voxel_count: voxel_count,
-            xyzis: xyzis,
-            palette: palette,
-        };
-        return Ok(vox);
+
+        write_tag(VOX_TAG, &mut writer)?;
+        write_int(self.version, &mut writer)?;
+        write_chunk(MAIN_TAG, &[], &children, &mut writer)?;
+        writer.flush()?;
+        return Ok(());
     }
 
-    /// Write object data to a file
-    pub fn write<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
-        let file = File::create(path)?;
-        let mut writer = BufWriter::new(file);
-
-        write_tag(VOX_TAG, &mut writer);
-        write_int(self.version, &mut writer);
-
-        write_tag(MAIN_TAG, &mut writer);
-        let size_chunk_size = INT_SIZE * 3; // x, y, z
-        let xyzi_chunk_size = INT_SIZE // voxel_count
-        + self.voxel_count as usize * XYZI_SIZE;
-        let rgba_chunk_size = PALETTE_RGBA_COUNT * RGBA_SIZE;
-        let main_child_chunks_size = ((TAG_SIZE + CHUNK_BYTE_COUNTS_SIZE) * 3)
-            + size_chunk_size
-            + xyzi_chunk_size
-            + rgba_chunk_size;
-        write_int(0, &mut writer); // MAIN has no content
-        write_int(main_child_chunks_size as i32, &mut writer);
-
-        write_tag(SIZE_TAG, &mut writer);
-        write_int(size_chunk_size as i32, &mut writer);
-        write_int(0, &mut writer); // SIZE has no children
-        write_int(self.x_size, &mut writer);
-        write_int(self.y_size, &mut writer);
-        write_int(self.z_size, &mut writer);
-
-        write_tag(XYZI_TAG, &mut writer);
-        write_int(xyzi_chunk_size as i32, &mut writer);
-        write_int(0, &mut writer); // XYZI has no children
-        write_int(self.voxel_count as i32, &mut writer);
-        for i in 0..self.voxel_count as usize {
-            write_xyzi(&self.xyzis[i], &mut writer);
+    /// Returns the bytes of a VOX file of the object data.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.write_to(&mut bytes)?;
+        return Ok(bytes);
+    }
+
+    /// Returns the index of the model placed by the nTRN node with `_name` `name`.
+    pub fn model_index(&self, name: &str) -> Option<usize> {
+        let child = self.nodes.iter().find_map(|node| match node {
+            Node::Transform { attributes, child, .. }
+                if attributes.iter().any(|(key, value)| key == "_name" && value == name) =>
+            {
+                Some(*child)
+            }
+            _ => None,
+        })?;
+        return self.nodes.iter().find_map(|node| match node {
+            Node::Shape { id, models, .. } if *id == child => {
+                models.first().map(|(model, _)| *model as usize)
+            }
+            _ => None,
+        });
+    }
+
+    /// Returns a `Vox` of only model `index`, keeping the palette and materials.
+    ///
+    /// The scene graph is dropped, as it refers to the other models.
+    pub fn model(&self, index: usize) -> Option<Self> {
+        let model = self.models.get(index)?.clone();
+        let mut vox = self.with_models(std::iter::once(model));
+        vox.nodes.clear();
+        return Some(vox);
+    }
+
+    /// Returns the value of `key` in the attributes of the root node of the
+    /// scene graph, if any.
+    pub fn root_attribute(&self, key: &str) -> Option<&str> {
+        let root = self.nodes.first()?;
+        let attribute = root.attributes().iter().find(|(attribute_key, _)| attribute_key == key);
+        return attribute.map(|(_, value)| value.as_str());
+    }
+
+    /// Sets `key` to `value` in the attributes of the root node of the scene
+    /// graph, doing nothing if there is no scene graph.
+    pub fn set_root_attribute(&mut self, key: &str, value: &str) {
+        if let Some(root) = self.nodes.first_mut() {
+            let attributes = root.attributes_mut();
+            attributes.retain(|(attribute_key, _)| attribute_key != key);
+            attributes.push((key.to_string(), value.to_string()));
         }
+    }
+
+    /// Returns the voxels of the first model.
+    pub fn xyzis(&self) -> &[[u8; XYZI_SIZE]] {
+        return &self.models[0].xyzis;
+    }
 
-        write_tag(RGBA_TAG, &mut writer);
-        write_int(rgba_chunk_size as i32, &mut writer);
-        write_int(0, &mut writer); // RGBA has no children
-        for i in 1..PALETTE_RGBA_COUNT {
-            write_rgba(&self.palette[i], &mut writer);
+    /// Returns the x, y and z size of each model.
+    pub fn sizes(&self) -> Vec<[usize; 3]> {
+        return Vec::from_iter(self.models.iter().map(|model| {
+            [model.x_size, model.y_size, model.z_size].map(|axis_size| axis_size as usize)
+        }));
+    }
+
+    pub fn palette(&self) -> &Palette {
+        return &self.palette;
+    }
+
+    /// Returns the number of voxels of each color index over every model.
+    pub fn color_counts(&self) -> [usize; PALETTE_RGBA_COUNT] {
+        let mut counts = [0; PALETTE_RGBA_COUNT];
+        for model in self.models.iter() {
+            for xyzi in model.xyzis.iter() {
+                counts[xyzi[3] as usize] += 1;
+            }
         }
-        return Ok(());
+        return counts;
     }
 
-    /// Return a new `Vox` rotated `rotation` degrees about the z axis
-    pub fn rotated(&self, rotation: &Rotation) -> Self {
-        let r = match rotation {
-            Rotation::R0 => [[1, 0, 0], [0, 1, 0], [0, 0, 1]],
-            Rotation::R90 => [[0, -1, 0], [1, 0, 0], [0, 0, 1]],
-            Rotation::R180 => [[-1, 0, 0], [0, -1, 0], [0, 0, 1]],
-            Rotation::R270 => [[0, 1, 0], [-1, 0, 0], [0, 0, 1]],
-        };
-        let mut rotated_xyzis = Vec::new();
-        let x_offset = self.x_size / 2;
-        let y_offset = self.y_size / 2;
-        let z_offset = self.z_size / 2;
-        let mut correction = (0, 0);
-        // Assumes tile is a cube
-        if self.x_size % 2 == 0 {
-            correction = match rotation {
-                Rotation::R0 => (0, 0),
-                Rotation::R90 => (1, 0),
-                Rotation::R180 => (1, 1),
-                Rotation::R270 => (0, 1),
+    /// Returns a new `Vox` using `palette`, where `remap` maps each color
+    /// index of this file to a color index of `palette`.
+    ///
+    /// Materials follow their colors, and only the first material mapped to
+    /// each new color index is kept.
+    pub fn remapped(&self, palette: &Palette, remap: &[u8; PALETTE_RGBA_COUNT]) -> Self {
+        let models = self.models.iter().map(|model| VoxModel {
+            xyzis: Vec::from_iter(
+                model
+                    .xyzis
+                    .iter()
+                    .map(|xyzi| [xyzi[0], xyzi[1], xyzi[2], remap[xyzi[3] as usize]]),
+            ),
+            ..model.clone()
+        });
+        let mut vox = self.with_models(models);
+        vox.palette = *palette;
+        vox.materials.clear();
+        for material in self.materials.iter() {
+            let id = match usize::try_from(material.id) {
+                Ok(index) if index < PALETTE_RGBA_COUNT && remap[index] != 0 => remap[index] as i32,
+                _ => continue,
             };
+            if !vox.materials.iter().any(|material| material.id == id) {
+                vox.materials.push(Material {
+                    id: id,
+                    properties: material.properties.clone(),
+                });
+            }
         }
-        for xyzi in self.xyzis.iter() {
-            let x = xyzi[0] as i32 - x_offset;
-            let y = xyzi[1] as i32 - y_offset;
-            let z = xyzi[2] as i32 - z_offset;
-            let rx = r[0][0] * x + r[0][1] * y + r[0][2] * z + x_offset - correction.0;
-            let ry = r[1][0] * x + r[1][1] * y + r[1][2] * z + y_offset - correction.1;
-            let rz = r[2][0] * x + r[2][1] * y + r[2][2] * z + z_offset;
-            rotated_xyzis.push([rx as u8, ry as u8, rz as u8, xyzi[3]]);
+        return vox;
+    }
+
+    /// Returns a `Vox` with this file's palette and materials holding
+    /// `voxels`, each a world coordinate and color index, in a world of `size`.
+    ///
+    /// The world is split into models of at most 256 voxels per axis, each
+    /// placed by an nTRN node under a single group.
+    pub fn with_voxels<I>(&self, size: [usize; 3], voxels: I) -> Self
+    where
+        I: IntoIterator<Item = ([usize; 3], u8)>,
+    {
+        let model_counts = size.map(|axis_size| axis_size.div_ceil(MAX_MODEL_SIZE));
+        let mut models = Vec::new();
+        let mut origins = Vec::new();
+        for z in 0..model_counts[2] {
+            for y in 0..model_counts[1] {
+                for x in 0..model_counts[0] {
+                    let origin = [x, y, z].map(|i| i * MAX_MODEL_SIZE);
+                    let model_size = [0, 1, 2].map(|axis| {
+                        std::cmp::min(MAX_MODEL_SIZE, size[axis] - origin[axis]) as i32
+                    });
+                    models.push(VoxModel {
+                        x_size: model_size[0],
+                        y_size: model_size[1],
+                        z_size: model_size[2],
+                        xyzis: Vec::new(),
+                    });
+                    origins.push(origin);
+                }
+            }
+        }
+        for (coordinate, color) in voxels {
+            if (0..3).any(|axis| coordinate[axis] >= size[axis]) {
+                continue;
+            }
+            let model_coordinate = coordinate.map(|i| i / MAX_MODEL_SIZE);
+            let model = model_coordinate[0]
+                + model_coordinate[1] * model_counts[0]
+                + model_coordinate[2] * model_counts[0] * model_counts[1];
+            let [x, y, z] = coordinate.map(|i| (i % MAX_MODEL_SIZE) as u8);
+            models[model].xyzis.push([x, y, z, color]);
+        }
+
+        // A root transform and group followed by a transform and shape per
+        // model, where `_t` is the world position of the model center
+        let mut nodes = vec![
+            Node::Transform {
+                id: 0,
+                attributes: Vec::new(),
+                child: 1,
+                reserved: -1,
+                layer: -1,
+                frames: vec![Vec::new()],
+            },
+            Node::Group {
+                id: 1,
+                attributes: Vec::new(),
+                children: Vec::from_iter((0..models.len()).map(|i| 2 + 2 * i as i32)),
+            },
+        ];
+        for (i, (model, origin)) in models.iter().zip(origins).enumerate() {
+            let id = 2 + 2 * i as i32;
+            let center = [
+                origin[0] as i32 + model.x_size / 2,
+                origin[1] as i32 + model.y_size / 2,
+                origin[2] as i32 + model.z_size / 2,
+            ];
+            let translation = format!("{} {} {}", center[0], center[1], center[2]);
+            nodes.push(Node::Transform {
+                id: id,
+                attributes: Vec::new(),
+                child: id + 1,
+                reserved: -1,
+                layer: 0,
+                frames: vec![vec![("_t".to_string(), translation)]],
+            });
+            nodes.push(Node::Shape {
+                id: id + 1,
+                attributes: Vec::new(),
+                models: vec![(i as i32, Vec::new())],
+            });
         }
         return Self {
             version: self.version,
-            x_size: self.x_size, // Assumes tile is a cube
-            y_size: self.y_size,
-            z_size: self.z_size,
-            voxel_count: self.voxel_count,
-            xyzis: rotated_xyzis,
-            palette: self.palette.clone(),
+            models: models,
+            nodes: nodes,
+            layers: vec![Layer {
+                id: 0,
+                attributes: Vec::new(),
+                reserved: -1,
+            }],
+            palette: self.palette,
+            materials: self.materials.clone(),
+            render_objects: Vec::new(),
+            other_chunks: Vec::new(),
         };
     }
 
-    /// Return a new `Vox` reflected about `axis`
+    /// Return a new `Vox` with every model rotated `rotation` degrees about the z axis
+    pub fn rotated(&self, rotation: &Rotation) -> Self {
+        return self.oriented(&Orientation::from(rotation));
+    }
+
+    /// Return a new `Vox` with every model turned by `orientation`
+    pub fn oriented(&self, orientation: &Orientation) -> Self {
+        return self.with_models(self.models.iter().map(|model| model.oriented(orientation)));
+    }
+
+    /// Return a new `Vox` with every model reflected about `axis`
     pub fn reflected(&self, axis: &Axis) -> Self {
-        let r = match axis {
-            Axis::X => (-1, 1),
-            Axis::Y => (1, -1),
+        return self.with_models(self.models.iter().map(|model| model.reflected(axis)));
+    }
+
+    /// Return a copy of this `Vox` with its models replaced by `models`.
+    fn with_models<I: Iterator<Item = VoxModel>>(&self, models: I) -> Self {
+        return Self {
+            version: self.version,
+            models: Vec::from_iter(models),
+            nodes: self.nodes.clone(),
+            layers: self.layers.clone(),
+            palette: self.palette,
+            materials: self.materials.clone(),
+            render_objects: self.render_objects.clone(),
+            other_chunks: self.other_chunks.clone(),
         };
-        let mut reflected_xyzis = Vec::new();
-        let x_offset = self.x_size / 2;
-        let y_offset = self.y_size / 2;
-        let z_offset = self.z_size / 2;
-        let mut correction = (0, 0);
-        // Assumes tile is a cube
-        if self.x_size % 2 == 0 {
-            correction = match axis {
-                Axis::X => (1, 0),
-                Axis::Y => (0, 1),
-            };
+    }
+}
+
+impl Node {
+    fn attributes(&self) -> &Dict {
+        return match self {
+            Node::Transform { attributes, .. } => attributes,
+            Node::Group { attributes, .. } => attributes,
+            Node::Shape { attributes, .. } => attributes,
+        };
+    }
+
+    fn attributes_mut(&mut self) -> &mut Dict {
+        return match self {
+            Node::Transform { attributes, .. } => attributes,
+            Node::Group { attributes, .. } => attributes,
+            Node::Shape { attributes, .. } => attributes,
+        };
+    }
+}
+
+impl VoxModel {
+    /// Return a new `VoxModel` turned by `orientation`, swapping sizes as the axes turn
+    pub fn oriented(&self, orientation: &Orientation) -> Self {
+        let sizes = [self.x_size, self.y_size, self.z_size];
+        // The source axis of each axis and whether it is reversed
+        let mut sources = [(0, false); 3];
+        for (axis, row) in orientation.matrix().iter().enumerate() {
+            let source = row.iter().position(|entry| *entry != 0).unwrap();
+            sources[axis] = (source, row[source] < 0);
         }
+        let mut oriented_xyzis = Vec::new();
         for xyzi in self.xyzis.iter() {
-            let x = xyzi[0] as i32 - x_offset;
-            let y = xyzi[1] as i32 - y_offset;
-            let z = xyzi[2] as i32 - z_offset;
-            let rx = r.0 * x + x_offset - correction.0;
-            let ry = r.1 * y + y_offset - correction.1;
-            let rz = z + z_offset;
-            reflected_xyzis.push([rx as u8, ry as u8, rz as u8, xyzi[3]]);
+            let mut oriented_xyzi = [0, 0, 0, xyzi[3]];
+            for (axis, (source, is_reversed)) in sources.iter().enumerate() {
+                let coordinate = xyzi[*source] as i32;
+                oriented_xyzi[axis] = match is_reversed {
+                    true => (sizes[*source] - 1 - coordinate) as u8,
+                    false => coordinate as u8,
+                };
+            }
+            oriented_xyzis.push(oriented_xyzi);
         }
         return Self {
-            version: self.version,
-            x_size: self.x_size, // Assumes tile is a cube
+            x_size: sizes[sources[0].0],
+            y_size: sizes[sources[1].0],
+            z_size: sizes[sources[2].0],
+            xyzis: oriented_xyzis,
+        };
+    }
+
+    /// Return a new `VoxModel` reflected about `axis`
+    pub fn reflected(&self, axis: &Axis) -> Self {
+        let (axis, size) = match axis {
+            Axis::X => (0, self.x_size),
+            Axis::Y => (1, self.y_size),
+        };
+        let mut reflected_xyzis = self.xyzis.clone();
+        for xyzi in reflected_xyzis.iter_mut() {
+            xyzi[axis] = (size - 1 - xyzi[axis] as i32) as u8;
+        }
+        return Self {
+            x_size: self.x_size,
             y_size: self.y_size,
             z_size: self.z_size,
-            voxel_count: self.voxel_count,
             xyzis: reflected_xyzis,
-            palette: self.palette.clone(),
         };
     }
 }
@@ -271,28 +942,159 @@ mod tests {
     #[test]
     fn test_read_vox() {
         let vox = Vox::open("tests/samples/concrete/config-1-road_turn_low.vox").unwrap();
-        vox.write("tests/samples/concrete/vox_test-0-road_turn_low.vox")
-            .unwrap();
-        assert!(true);
+        std::fs::create_dir_all("tests/output").unwrap();
+        vox.write("tests/output/vox_test-0-road_turn_low.vox").unwrap();
+        let written = Vox::open("tests/output/vox_test-0-road_turn_low.vox").unwrap();
+        assert_eq!(written.to_bytes().unwrap(), vox.to_bytes().unwrap());
     }
 
     #[test]
     fn test_rotate_90_z() {
         let vox = Vox::open("tests/samples/vox_test-1-road_turn_low.vox").unwrap();
         let rotated_vox = vox.rotated(&Rotation::R90);
-        rotated_vox
-            .write("tests/samples/vox_test-1-road_turn_low_r90.vox")
-            .unwrap();
-        assert!(true);
+        assert_ne!(rotated_vox.to_bytes().unwrap(), vox.to_bytes().unwrap());
+        let unrotated_vox = rotated_vox.rotated(&Rotation::R270);
+        assert_eq!(unrotated_vox.to_bytes().unwrap(), vox.to_bytes().unwrap());
     }
 
     #[test]
     fn test_reflect_y() {
         let vox = Vox::open("tests/samples/vox_test-2-path_stair_0b.vox").unwrap();
-        let rotated_vox = vox.reflected(&Axis::Y);
-        rotated_vox
-            .write("tests/samples/vox_test-2-path_stair_0b_ry.vox")
-            .unwrap();
-        assert!(true);
+        let reflected_vox = vox.reflected(&Axis::Y);
+        assert_ne!(reflected_vox.to_bytes().unwrap(), vox.to_bytes().unwrap());
+        let unreflected_vox = reflected_vox.reflected(&Axis::Y);
+        assert_eq!(unreflected_vox.to_bytes().unwrap(), vox.to_bytes().unwrap());
+    }
+
+    #[test]
+    fn test_open_malformed() {
+        let result = Vox::open("tests/samples/stairs/config.json");
+        assert!(matches!(result, Err(Error::MalformedVox { offset: 0, .. })));
+    }
+
+    #[test]
+    fn test_round_trip_scene() {
+        let path = "tests/samples/stairs/stairs.vox";
+        let vox = Vox::open(path).unwrap();
+        assert_eq!(vox.models.len(), 4);
+        assert!(!vox.nodes.is_empty());
+        assert!(!vox.materials.is_empty());
+        std::fs::create_dir_all("tests/output").unwrap();
+        vox.write("tests/output/vox_test-round_trip.vox").unwrap();
+        let written = std::fs::read("tests/output/vox_test-round_trip.vox").unwrap();
+        assert_eq!(written, std::fs::read(path).unwrap());
+    }
+
+    #[test]
+    fn test_default_palette() {
+        let vox = Vox::open("tests/samples/vox_test-3-no_palette.vox").unwrap();
+        assert_eq!(vox.palette[1], [255, 255, 255, 255]);
+        assert_eq!(vox.palette[2], [255, 255, 204, 255]);
+        assert_eq!(vox.palette[255], [17, 17, 17, 255]);
+    }
+
+    #[test]
+    fn test_model() {
+        let vox = Vox::open("tests/samples/stairs/stairs.vox").unwrap();
+        assert_eq!(vox.model_index("empty"), Some(0));
+        assert_eq!(vox.model_index("stairs_1"), Some(3));
+        assert_eq!(vox.model_index("missing"), None);
+        let model = vox.model(3).unwrap();
+        assert_eq!(model.models, vec![vox.models[3].clone()]);
+        assert!(model.nodes.is_empty());
+        assert!(vox.model(4).is_none());
+    }
+
+    #[test]
+    fn test_with_voxels() {
+        let vox = Vox::open("tests/samples/stairs/stairs-0-empty.vox").unwrap();
+        let voxels = [([0, 0, 0], 1), ([299, 9, 4], 2), ([300, 0, 0], 3)];
+        let merged = vox.with_voxels([300, 10, 5], voxels);
+        assert_eq!(merged.models.len(), 2);
+        assert_eq!(merged.models[0].xyzis, vec![[0, 0, 0, 1]]);
+        assert_eq!(merged.models[1].x_size, 44);
+        assert_eq!(merged.models[1].xyzis, vec![[43, 9, 4, 2]]);
+        assert_eq!(merged.nodes.len(), 6);
+        assert_eq!(merged.palette, vox.palette);
+
+        let mut merged = merged;
+        assert_eq!(merged.root_attribute("_periodic"), None);
+        merged.set_root_attribute("_periodic", "x");
+        merged.set_root_attribute("_periodic", "x y");
+        let merged = Vox::from_bytes(&merged.to_bytes().unwrap()).unwrap();
+        assert_eq!(merged.root_attribute("_periodic"), Some("x y"));
+    }
+
+    #[test]
+    fn test_oriented() {
+        let model = VoxModel {
+            x_size: 2,
+            y_size: 3,
+            z_size: 4,
+            xyzis: vec![[1, 2, 3, 1]],
+        };
+        let rotated = model.oriented(&Orientation::from(&Rotation::R90));
+        assert_eq!((rotated.x_size, rotated.y_size, rotated.z_size), (3, 2, 4));
+        assert_eq!(rotated.xyzis, vec![[0, 1, 3, 1]]);
+        let vox = Vox::open("tests/samples/stairs/stairs-2-stairs_0.vox").unwrap();
+        let turned = (0..4).fold(vox.clone(), |turned, _| turned.rotated(&Rotation::R90));
+        assert_eq!(turned, vox);
+    }
+
+    #[test]
+    fn test_odd_sizes() {
+        let model = VoxModel {
+            x_size: 3,
+            y_size: 5,
+            z_size: 1,
+            xyzis: vec![[0, 1, 0, 1], [2, 4, 0, 2]],
+        };
+        let vox = Vox::open("tests/samples/stairs/stairs-0-empty.vox").unwrap();
+        let vox = vox.with_models(std::iter::once(model));
+        let rotated = vox.rotated(&Rotation::R90);
+        assert_eq!(rotated.sizes(), vec![[5, 3, 1]]);
+        assert_eq!(rotated.models[0].xyzis, vec![[3, 0, 0, 1], [0, 2, 0, 2]]);
+        let reflected = vox.reflected(&Axis::X);
+        assert_eq!(reflected.models[0].xyzis, vec![[2, 1, 0, 1], [0, 4, 0, 2]]);
+        assert_eq!(reflected.reflected(&Axis::X), vox);
+        let bytes = rotated.to_bytes().unwrap();
+        assert_eq!(Vox::from_bytes(&bytes).unwrap(), rotated);
+    }
+
+    #[test]
+    fn test_voxel_outside() {
+        let mut bytes = std::fs::read("tests/samples/stairs/stairs-1-path.vox").unwrap();
+        let xyzi = bytes.windows(4).position(|tag| tag == b"XYZI").unwrap();
+        bytes[xyzi + 16] = 200;
+        let result = Vox::from_bytes(&bytes);
+        assert!(matches!(result, Err(Error::MalformedVox { offset, .. }) if offset == xyzi as u64 + 16));
+    }
+
+    #[test]
+    fn test_bytes() {
+        let bytes = std::fs::read("tests/samples/abstract/abstract.vox").unwrap();
+        let vox = Vox::from_bytes(&bytes).unwrap();
+        assert_eq!(vox.to_bytes().unwrap(), bytes);
+        assert_eq!(Vox::from_bytes(&vox.to_bytes().unwrap()).unwrap(), vox);
+    }
+
+    #[test]
+    fn test_versions() {
+        let mut bytes = std::fs::read("tests/samples/stairs/stairs.vox").unwrap();
+        bytes[TAG_SIZE..TAG_SIZE + INT_SIZE].copy_from_slice(&200i32.to_le_bytes());
+        let vox = Vox::from_bytes(&bytes).unwrap();
+        assert_eq!(vox.to_bytes().unwrap(), bytes);
+        bytes[TAG_SIZE..TAG_SIZE + INT_SIZE].copy_from_slice(&149i32.to_le_bytes());
+        let result = Vox::from_bytes(&bytes);
+        assert!(matches!(result, Err(Error::MalformedVox { offset: 4, .. })));
+    }
+
+    #[test]
+    fn test_truncated() {
+        let bytes = std::fs::read("tests/samples/abstract/abstract.vox").unwrap();
+        for size in [0, 6, 30, bytes.len() / 2, bytes.len() - 1] {
+            let result = Vox::from_bytes(&bytes[..size]);
+            assert!(matches!(result, Err(Error::Io(ref e)) if e.kind() == io::ErrorKind::UnexpectedEof));
+        }
     }
 }
@@ -1,21 +1,106 @@
-use std::collections::{HashMap, HashSet};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
 
-use rand;
-use rand::rngs::ThreadRng;
 use rand::seq::SliceRandom;
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 
+use super::bitset::BitSet;
 use super::model::Face;
 
+/// The scale of the noise added to entropies to break ties.
+const NOISE_SCALE: f64 = 1e-6;
+
 #[derive(Debug)]
 /// A container to hold the state of `wfc` waves.
+///
+/// Propogation follows AC-4: every wave keeps a count of the tiles in each
+/// neighboring wave that support each of its tiles, and a tile is removed
+/// once any of its counts reaches zero.
 pub struct Waves<'a> {
     graph: &'a Vec<Vec<(usize, Face)>>,
-    constraints: &'a HashMap<Face, Vec<HashSet<usize>>>,
+    propagator: Vec<Vec<Vec<usize>>>,
+    weights: &'a [f64],
+    weight_log_weights: Vec<f64>,
+    heuristic: Heuristic,
     collapsed_count: usize,
-    rng: ThreadRng,
-    entropies: Vec<f32>,
-    tiles: Vec<HashSet<usize>>,
+    rng: ChaCha8Rng,
+    collapsed: Vec<bool>,
+    noise: Vec<f64>,
+    sums_of_weights: Vec<f64>,
+    sums_of_weight_log_weights: Vec<f64>,
+    tile_count: usize,
+    counts: Vec<usize>,
+    supports: Vec<u32>,
+    tiles: Vec<BitSet>,
+    pending: Vec<(usize, usize)>,
+    backtrack_depth: usize,
+    trail: Vec<Change>,
+    decisions: Vec<Decision>,
+    /// Uncollapsed waves keyed by their entropy when pushed, which go stale
+    /// once the entropy of their wave changes.
+    candidates: BinaryHeap<Candidate>,
+    /// Whether the entropy of each wave changed since it was last pushed to
+    /// `candidates`.
+    changed: Vec<bool>,
+    changed_waves: Vec<usize>,
+    /// The first wave that may be uncollapsed.
+    scanline: usize,
+    /// The uncollapsed waves in no particular order, and the position of
+    /// each wave in it.
+    uncollapsed: Vec<usize>,
+    uncollapsed_positions: Vec<usize>,
+}
+
+#[derive(Debug)]
+/// A reversible change to the state of a wave.
+enum Change {
+    Removed { wave: usize, tile: usize },
+    Propogated { wave: usize, tile: usize },
+    Collapsed { wave: usize },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A strategy for choosing the next wave to observe.
+pub enum Heuristic {
+    /// The wave with the fewest remaining tiles.
+    MinCount,
+    /// The wave with the minimum Shannon entropy of its weighted tiles.
+    Shannon,
+    /// The first uncollapsed wave in x, y, z order.
+    Scanline,
+    /// An uncollapsed wave chosen uniformly at random.
+    Random,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// A wave to observe next, ordered so that the lowest entropy, and then the
+/// lowest wave, is at the top of a `BinaryHeap`.
+struct Candidate {
+    entropy: f64,
+    wave: usize,
+}
+
+impl Eq for Candidate {}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        return other.entropy.total_cmp(&self.entropy).then(other.wave.cmp(&self.wave));
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+#[derive(Debug)]
+/// An observation that can be undone by backtracking.
+struct Decision {
+    wave: usize,
+    tile: usize,
+    trail_len: usize,
 }
 
 #[derive(Debug)]
@@ -24,160 +109,460 @@ pub struct Contradiction {
     pub wave: usize,
     pub tiles: HashSet<usize>,
     pub face: Face,
+    /// The names of `tiles` in tile order, empty until `named` is called.
+    pub names: Vec<String>,
+}
+
+impl Contradiction {
+    /// Returns this contradiction with the names of its tiles looked up in `names`.
+    pub fn named(self, names: &[String]) -> Self {
+        let mut tiles = Vec::from_iter(self.tiles.iter().copied());
+        tiles.sort_unstable();
+        let names = tiles.into_iter().map(|tile| names[tile].clone()).collect();
+        return Contradiction { names: names, ..self };
+    }
 }
 
 impl<'a> Waves<'a> {
-    /// Constructs an uncollapsed `Waves`.
+    /// Constructs an uncollapsed `Waves` whose random choices are driven by `seed`.
+    ///
+    /// Tiles are observed in proportion to `weights`. Tiles that a neighbor
+    /// can never support are removed up front, which fails if a wave is left
+    /// without tiles.
     pub fn new(
         graph: &'a Vec<Vec<(usize, Face)>>,
-        constraints: &'a HashMap<Face, Vec<HashSet<usize>>>,
-    ) -> Self {
+        constraints: &HashMap<Face, Vec<BitSet>>,
+        weights: &'a [f64],
+        seed: u64,
+    ) -> Result<Self, Contradiction> {
         let wave_count = graph.len();
         let tile_count = constraints[&Face::Left].len();
         let collapsed_count = 0;
-        let mut rng = rand::thread_rng();
-        let mut entropies = vec![tile_count as f32; wave_count];
-        for entropy in entropies.iter_mut() {
-            *entropy += rng.gen::<f32>(); // Add noise to break min entropy ties
-        }
-        let mut all_tiles = HashSet::new();
-        for tile in 0..tile_count {
-            all_tiles.insert(tile);
-        }
-        let tiles = vec![all_tiles; wave_count];
-        return Self {
-            graph: &graph,
-            constraints: &constraints,
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let mut noise = vec![0.0; wave_count];
+        for n in noise.iter_mut() {
+            *n = rng.gen::<f64>() * NOISE_SCALE; // Add noise to break min entropy ties
+        }
+        let weight_log_weights = Vec::from_iter(weights.iter().map(|weight| weight * weight.ln()));
+        let sum_of_weights = weights.iter().sum();
+        let sum_of_weight_log_weights = weight_log_weights.iter().sum();
+
+        // For each face, the tiles each tile allows on that face and the
+        // number of tiles that allow each tile
+        let mut propagator = Vec::new();
+        let mut support_counts = Vec::new();
+        for face in Face::ALL {
+            let face_propagator = Vec::from_iter(
+                constraints[&face]
+                    .iter()
+                    .map(|valid_tiles| Vec::from_iter(valid_tiles.iter())),
+            );
+            let mut face_support_counts = vec![0; tile_count];
+            for valid_tiles in face_propagator.iter() {
+                for tile in valid_tiles.iter() {
+                    face_support_counts[*tile] += 1;
+                }
+            }
+            propagator.push(face_propagator);
+            support_counts.push(face_support_counts);
+        }
+
+        let mut waves = Self {
+            graph: graph,
+            propagator: propagator,
+            weights: weights,
+            weight_log_weights: weight_log_weights,
+            heuristic: Heuristic::Shannon,
             collapsed_count: collapsed_count,
-            entropies: entropies,
-            tiles: tiles,
+            collapsed: vec![false; wave_count],
+            noise: noise,
+            sums_of_weights: vec![sum_of_weights; wave_count],
+            sums_of_weight_log_weights: vec![sum_of_weight_log_weights; wave_count],
+            tile_count: tile_count,
+            counts: vec![tile_count; wave_count],
+            supports: vec![0; wave_count * tile_count * Face::ALL.len()],
+            tiles: vec![BitSet::full(tile_count); wave_count],
+            pending: Vec::new(),
             rng: rng,
+            backtrack_depth: 0,
+            trail: Vec::new(),
+            decisions: Vec::new(),
+            candidates: BinaryHeap::new(),
+            changed: vec![true; wave_count],
+            changed_waves: Vec::from_iter(0..wave_count),
+            scanline: 0,
+            uncollapsed: Vec::from_iter(0..wave_count),
+            uncollapsed_positions: Vec::from_iter(0..wave_count),
         };
+        for edges in graph.iter() {
+            for (edge_wave, edge_face) in edges.iter() {
+                let face = edge_face.index();
+                for (tile, support_count) in support_counts[face].iter().enumerate() {
+                    let support = waves.support(*edge_wave, tile, face);
+                    waves.supports[support] = *support_count;
+                }
+            }
+        }
+        for (wave, edges) in graph.iter().enumerate() {
+            for (edge_wave, edge_face) in edges.iter() {
+                let face = edge_face.index();
+                for (tile, support_count) in support_counts[face].iter().enumerate() {
+                    if *support_count == 0 {
+                        waves.remove(*edge_wave, tile);
+                    }
+                }
+                if waves.counts[*edge_wave] == 0 {
+                    return Err(waves.contradiction(wave, edge_face));
+                }
+            }
+        }
+        waves.propogate()?;
+        return Ok(waves);
     }
 
-    /// Returns the minimum entropy wave.
-    pub fn min_entropy_wave(&self) -> usize {
-        let mut min_entropy_wave = 0;
-        let mut min_entropy = f32::MAX;
-        for (wave, entropy) in self.entropies.iter().enumerate() {
-            if *entropy > 0.0 && *entropy < min_entropy {
-                // println!("found min entropy {} {}", wave, *entropy);
-                min_entropy_wave = wave;
-                min_entropy = *entropy;
+    /// Enables backtracking over at most `depth` of the most recent observations.
+    ///
+    /// A `depth` of 0 disables backtracking, so the first contradiction is final.
+    pub fn set_backtrack_depth(&mut self, depth: usize) {
+        self.backtrack_depth = depth;
+    }
+
+    /// Sets the `heuristic` used to choose the next wave to observe.
+    pub fn set_heuristic(&mut self, heuristic: Heuristic) {
+        self.heuristic = heuristic;
+        self.candidates.clear();
+        for wave in 0..self.collapsed.len() {
+            self.mark_changed(wave);
+        }
+    }
+
+    /// Records that the entropy of `wave` changed.
+    fn mark_changed(&mut self, wave: usize) {
+        if !self.changed[wave] {
+            self.changed[wave] = true;
+            self.changed_waves.push(wave);
+        }
+    }
+
+    /// Returns the index in `supports` of the support count of `tile` in
+    /// `wave` from the neighbor that sees `wave` on `face`.
+    fn support(&self, wave: usize, tile: usize, face: usize) -> usize {
+        return (wave * self.tile_count + tile) * Face::ALL.len() + face;
+    }
+
+    /// Returns the next uncollapsed wave to observe according to the heuristic.
+    pub fn next_wave(&mut self) -> usize {
+        return match self.heuristic {
+            Heuristic::MinCount => self.min_entropy_wave(|waves, wave| waves.counts[wave] as f64),
+            Heuristic::Shannon => self.min_entropy_wave(Waves::shannon_entropy),
+            Heuristic::Scanline => {
+                while self.scanline < self.collapsed.len() && self.collapsed[self.scanline] {
+                    self.scanline += 1;
+                }
+                if self.scanline < self.collapsed.len() {
+                    self.scanline
+                } else {
+                    0
+                }
             }
+            Heuristic::Random => *self.uncollapsed.choose(&mut self.rng).unwrap_or(&0),
+        };
+    }
+
+    /// Returns the uncollapsed wave with the minimum `entropy`, breaking ties
+    /// by the lowest wave.
+    ///
+    /// Only waves whose entropy changed since the last call are pushed to
+    /// `candidates`, and stale candidates are dropped as they reach the top.
+    fn min_entropy_wave(&mut self, entropy: fn(&Self, usize) -> f64) -> usize {
+        while let Some(wave) = self.changed_waves.pop() {
+            self.changed[wave] = false;
+            if !self.collapsed[wave] {
+                let entropy = entropy(self, wave) + self.noise[wave];
+                self.candidates.push(Candidate { entropy: entropy, wave: wave });
+            }
+        }
+        while let Some(candidate) = self.candidates.peek() {
+            let wave = candidate.wave;
+            let is_current = entropy(self, wave) + self.noise[wave] == candidate.entropy;
+            if !self.collapsed[wave] && is_current {
+                return wave;
+            }
+            self.candidates.pop();
         }
-        return min_entropy_wave;
+        return 0;
+    }
+
+    /// Returns the Shannon entropy of the weighted tiles of `wave`.
+    fn shannon_entropy(&self, wave: usize) -> f64 {
+        let sum_of_weights = self.sums_of_weights[wave];
+        return sum_of_weights.ln() - self.sums_of_weight_log_weights[wave] / sum_of_weights;
     }
 
-    /// Picks a tile at random from the tiles of `wave`.
-    pub fn observe(&mut self, wave: usize) {
-        let tiles = Vec::from_iter(self.tiles[wave].clone());
-        let observed_tile = tiles.choose(&mut self.rng).unwrap();
-        self.tiles[wave] = HashSet::from([*observed_tile]);
+    /// Picks a tile at random from the tiles of `wave` in proportion to its
+    /// weight and returns it, or `None` if `wave` has no tiles.
+    pub fn observe(&mut self, wave: usize) -> Option<usize> {
+        let tiles = Vec::from_iter(self.tiles[wave].iter());
+        let weights = self.weights;
+        let observed_tile = *tiles
+            .choose_weighted(&mut self.rng, |tile| weights[*tile])
+            .ok()?;
+        if self.backtrack_depth > 0 {
+            self.decisions.push(Decision {
+                wave: wave,
+                tile: observed_tile,
+                trail_len: self.trail.len(),
+            });
+            if self.decisions.len() > self.backtrack_depth {
+                self.forget_oldest_decision();
+            }
+        }
+        for tile in tiles {
+            if tile != observed_tile {
+                self.remove(wave, tile);
+            }
+        }
         self.collapse(wave);
+        return Some(observed_tile);
     }
 
     /// Marks a wave as collapsed.
     fn collapse(&mut self, wave: usize) {
-        self.entropies[wave] = 0.0;
+        if self.backtrack_depth > 0 {
+            self.trail.push(Change::Collapsed { wave: wave });
+        }
+        self.collapsed[wave] = true;
         self.collapsed_count += 1;
+        let position = self.uncollapsed_positions[wave];
+        self.uncollapsed.swap_remove(position);
+        if let Some(moved_wave) = self.uncollapsed.get(position) {
+            self.uncollapsed_positions[*moved_wave] = position;
+        }
     }
 
-    /// Propogates constraints over graph starting from `wave`.
-    pub fn propogate(&mut self, wave: usize) -> Result<(), Contradiction> {
-        let mut stack = vec![wave];
-        let mut visited = HashSet::new();
-        // let mut observed_waves = HashSet::new();
-        while let Some(wave) = stack.pop() {
-            visited.insert(wave);
-            // TODO: Spawn a new thread for each face
-            // TODO: Benchmark to determine which of the following optimizations is better:
-            // 1. Observe tiles which only have 1 possible tile after propogation
-            // 2. Stop propogating when we reach an unchanged tile
-            // Due to bugs in approach 1, we are going with approach 2 for now
-            // let mut initial_tile_counts = Vec::new();
-            for (edge_wave, edge_face) in self.graph[wave].iter() {
-                if !visited.contains(edge_wave) && self.entropies[*edge_wave] > 0.0 {
-                    //initial_tile_counts.push((*edge_wave, self.tiles[*edge_wave].len()));
-                    let initial_tile_count = self.tiles[*edge_wave].len();
-                    self.constrain(*edge_wave, self.constraints, wave, edge_face);
-                    if self.tiles[*edge_wave].is_empty() {
-                        return Err(
-                            Contradiction {
-                                wave: wave,
-                                tiles: self.tiles[wave].clone(),
-                                face: edge_face.clone(),
-                            }
-                        );
-                    }
-                    if self.tiles[*edge_wave].len() != initial_tile_count {
-                        stack.push(*edge_wave);
+    /// Removes `tile` from `wave` and queues the removal for propogation.
+    fn remove(&mut self, wave: usize, tile: usize) {
+        if self.tiles[wave].remove(tile) {
+            self.counts[wave] -= 1;
+            self.sums_of_weights[wave] -= self.weights[tile];
+            self.sums_of_weight_log_weights[wave] -= self.weight_log_weights[tile];
+            self.pending.push((wave, tile));
+            self.mark_changed(wave);
+            if self.backtrack_depth > 0 {
+                self.trail.push(Change::Removed { wave: wave, tile: tile });
+            }
+        }
+    }
+
+    /// Drops the oldest observation so it can no longer be backtracked.
+    fn forget_oldest_decision(&mut self) {
+        self.decisions.remove(0);
+        let trail_start = self.decisions[0].trail_len;
+        self.trail.drain(..trail_start);
+        for decision in self.decisions.iter_mut() {
+            decision.trail_len -= trail_start;
+        }
+    }
+
+    /// Reverts every change made after the trail had `trail_len` entries.
+    fn undo(&mut self, trail_len: usize) {
+        let graph = self.graph;
+        while self.trail.len() > trail_len {
+            match self.trail.pop().unwrap() {
+                Change::Removed { wave, tile } => {
+                    self.tiles[wave].insert(tile);
+                    self.counts[wave] += 1;
+                    self.sums_of_weights[wave] += self.weights[tile];
+                    self.sums_of_weight_log_weights[wave] += self.weight_log_weights[tile];
+                    self.mark_changed(wave);
+                }
+                Change::Propogated { wave, tile } => {
+                    for (edge_wave, edge_face) in graph[wave].iter() {
+                        let face = edge_face.index();
+                        for i in 0..self.propagator[face][tile].len() {
+                            let edge_tile = self.propagator[face][tile][i];
+                            let support = self.support(*edge_wave, edge_tile, face);
+                            self.supports[support] += 1;
+                        }
                     }
                 }
-                // For approach 1, we must always push unvisited edges
-                // if !visited.contains(edge_wave) {
-                //     stack.push(*edge_wave);
-                // }
-            }
-            // for (edge_wave, initial_tile_count) in initial_tile_counts {
-            //     if self.tiles[edge_wave].is_empty() {
-            //         return Err(
-            //             Contradiction {
-            //                 wave: wave,
-            //                 tiles: self.tiles[wave],
-
-            //             }
-            //         );
-            //     }
-            //     // if self.tiles[edge_wave].len() == 1 {
-            //     //     if self.entropies[edge_wave] < 1.0 {
-            //     //         println!("unreachable");
-            //     //     }
-            //     //     observed_waves.insert(edge_wave);
-            //     // }
-            //     if self.tiles[edge_wave].len() != initial_tile_count {
-            //         stack.push(edge_wave);
-            //     }
-            // }
-        }
-        // for wave in observed_waves.iter() {
-        //     self.collapse(*wave);
-        // }
-        return Ok(());
+                Change::Collapsed { wave } => {
+                    self.collapsed[wave] = false;
+                    self.collapsed_count -= 1;
+                    self.uncollapsed_positions[wave] = self.uncollapsed.len();
+                    self.uncollapsed.push(wave);
+                    self.scanline = self.scanline.min(wave);
+                    self.mark_changed(wave);
+                }
+            }
+        }
     }
 
-    /// Constrains `edge_wave` with `constraints` of `wave` on `edge_face`.
-    fn constrain(
-        &mut self,
-        edge_wave: usize,
-        constraints: &HashMap<Face, Vec<HashSet<usize>>>,
-        wave: usize,
-        edge_face: &Face,
-    ) {
-        let constraints = &constraints[edge_face];
-        let mut valid_tiles = HashSet::new();
-        for tile in self.tiles[wave].iter() {
-            for valid_tile in constraints[*tile].iter() {
-                valid_tiles.insert(*valid_tile);
+    /// Recovers from `contradiction` by undoing the most recent observations.
+    ///
+    /// The observed tile of each undone observation is ruled out for its wave
+    /// and the removal propogated, so the next observation tries an
+    /// alternative. Returns `contradiction` if there is nothing left to undo.
+    pub fn backtrack(&mut self, contradiction: Contradiction) -> Result<(), Contradiction> {
+        let mut contradiction = contradiction;
+        while let Some(decision) = self.decisions.pop() {
+            self.pending.clear();
+            self.undo(decision.trail_len);
+            self.remove(decision.wave, decision.tile);
+            if self.counts[decision.wave] == 0 {
+                continue;
+            }
+            match self.propogate() {
+                Ok(()) => return Ok(()),
+                Err(c) => contradiction = c,
+            }
+        }
+        return Err(contradiction);
+    }
+
+    /// Propogates every pending tile removal over the graph.
+    ///
+    /// Each removal decrements the support counts of the tiles it allowed in
+    /// neighboring waves, removing those tiles once their support runs out.
+    pub fn propogate(&mut self) -> Result<(), Contradiction> {
+        let graph = self.graph;
+        while let Some((wave, tile)) = self.pending.pop() {
+            // Finish every decrement of a removal before reporting a
+            // contradiction so that undoing it restores consistent supports
+            let mut contradiction = None;
+            for (edge_wave, edge_face) in graph[wave].iter() {
+                let face = edge_face.index();
+                for i in 0..self.propagator[face][tile].len() {
+                    let edge_tile = self.propagator[face][tile][i];
+                    let support = self.support(*edge_wave, edge_tile, face);
+                    self.supports[support] -= 1;
+                    if self.supports[support] == 0 {
+                        self.remove(*edge_wave, edge_tile);
+                        if self.counts[*edge_wave] == 0 && contradiction.is_none() {
+                            contradiction = Some(self.contradiction(wave, edge_face));
+                        }
+                    }
+                }
+            }
+            if self.backtrack_depth > 0 {
+                self.trail.push(Change::Propogated { wave: wave, tile: tile });
+            }
+            if let Some(contradiction) = contradiction {
+                self.pending.clear();
+                return Err(contradiction);
             }
         }
-        let tiles = self.tiles[edge_wave].clone();
-        println!("{:?}", tiles);
-        for tile in tiles.iter() {
-            if !valid_tiles.contains(tile) {
-                self.entropies[edge_wave] -= 1.0;
-                self.tiles[edge_wave].remove(tile);
+        return Ok(());
+    }
+
+    /// Removes every tile of `wave` not in `allowed` and queues the removals
+    /// for propogation. Returns false if `wave` is left without tiles.
+    pub fn restrict(&mut self, wave: usize, allowed: &BitSet) -> bool {
+        let tiles = Vec::from_iter(self.tiles[wave].iter());
+        for tile in tiles {
+            if !allowed.contains(tile) {
+                self.remove(wave, tile);
             }
         }
+        return self.counts[wave] > 0;
+    }
+
+    /// Returns a `Contradiction` for the tiles of `wave` failing to support `face`.
+    fn contradiction(&self, wave: usize, face: &Face) -> Contradiction {
+        return Contradiction {
+            wave: wave,
+            tiles: HashSet::from_iter(self.tiles[wave].iter()),
+            face: face.clone(),
+            names: Vec::new(),
+        };
     }
 
     /// Returns true if all waves are collapsed
     pub fn are_collapsed(&self) -> bool {
-        return self.collapsed_count == self.entropies.len();
+        return self.collapsed_count == self.collapsed.len();
+    }
+
+    /// Returns the whole percentage of collapsed waves.
+    pub fn progress(&self) -> usize {
+        if self.collapsed.is_empty() {
+            return 100;
+        }
+        return self.collapsed_count * 100 / self.collapsed.len();
     }
 
     /// Returns the current `tiles` state of all waves
-    pub fn tiles(&self) -> &Vec<HashSet<usize>> {
+    pub fn tiles(&self) -> &Vec<BitSet> {
         return &self.tiles;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::tile::Tiles;
+    use super::*;
+
+    /// Returns the edges of a `width` by `depth` grid of waves.
+    fn grid_graph(width: usize, depth: usize) -> Vec<Vec<(usize, Face)>> {
+        let mut graph = Vec::new();
+        for y in 0..depth {
+            for x in 0..width {
+                let mut edges = Vec::new();
+                if x > 0 {
+                    edges.push((x - 1 + y * width, Face::Left));
+                }
+                if x < width - 1 {
+                    edges.push((x + 1 + y * width, Face::Right));
+                }
+                if y > 0 {
+                    edges.push((x + (y - 1) * width, Face::Front));
+                }
+                if y < depth - 1 {
+                    edges.push((x + (y + 1) * width, Face::Back));
+                }
+                graph.push(edges);
+            }
+        }
+        return graph;
+    }
+
+    #[test]
+    fn test_next_wave() {
+        let mut tiles = Tiles::from("tests/samples/stairs").unwrap();
+        tiles.generate_transformed_tiles();
+        let constraints = tiles.constraints();
+        let graph = grid_graph(8, 8);
+        let heuristics = [Heuristic::MinCount, Heuristic::Shannon, Heuristic::Scanline, Heuristic::Random];
+        for heuristic in heuristics {
+            let mut waves = Waves::new(&graph, &constraints, tiles.weights(), 3).unwrap();
+            waves.set_backtrack_depth(16);
+            waves.set_heuristic(heuristic);
+            while !waves.are_collapsed() {
+                let wave = waves.next_wave();
+                let uncollapsed = Vec::from_iter((0..graph.len()).filter(|wave| !waves.collapsed[*wave]));
+                let entropy = |wave: &&usize| match heuristic {
+                    Heuristic::MinCount => waves.counts[**wave] as f64 + waves.noise[**wave],
+                    _ => waves.shannon_entropy(**wave) + waves.noise[**wave],
+                };
+                let expected = match heuristic {
+                    Heuristic::Scanline => uncollapsed[0],
+                    Heuristic::Random => wave,
+                    _ => *uncollapsed.iter().min_by(|a, b| entropy(a).total_cmp(&entropy(b))).unwrap(),
+                };
+                assert!(uncollapsed.contains(&wave));
+                assert_eq!(wave, expected);
+                waves.observe(wave).unwrap();
+                if let Err(c) = waves.propogate() {
+                    waves.backtrack(c).unwrap();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_observe_without_tiles() {
+        let constraints = HashMap::from_iter(Face::ALL.map(|face| (face, Vec::new())));
+        let graph = grid_graph(1, 1);
+        let mut waves = Waves::new(&graph, &constraints, &[], 0).unwrap();
+        assert_eq!(waves.observe(0), None);
+    }
+}
@@ -4,9 +4,11 @@ fn test_concrete() {
     let width = 16;
     let depth = 16;
     let height = 5;
+    let seed = 0;
+    let policy = wfc::RetryPolicy::default();
     let output_file = "tests/output/concrete.txt";
-    wfc::run(sample_dir, width, depth, height, output_file);
-    assert!(true);
+    wfc::run(sample_dir, width, depth, height, output_file, seed, &policy).unwrap();
+    assert!(std::path::Path::new(output_file).exists());
 }
 
 #[test]
@@ -15,9 +17,12 @@ fn test_concrete_2() {
     let width = 16;
     let depth = 16;
     let height = 16; //16;
+    let seed = 0;
     let output_file = "tests/output/concrete_2.txt";
-    wfc::debug(sample_dir, width, depth, height, output_file);
-    assert!(true);
+    match wfc::debug(sample_dir, width, depth, height, output_file, seed) {
+        Ok(()) | Err(wfc::Error::Unsatisfiable(_)) => (),
+        Err(e) => panic!("{}", e),
+    }
 }
 
 #[test]
@@ -26,9 +31,11 @@ fn test_concrete_3() {
     let width = 32;
     let depth = 32;
     let height = 5;
+    let seed = 0;
+    let policy = wfc::RetryPolicy::default();
     let output_file = "tests/output/concrete_3.txt";
-    wfc::run(sample_dir, width, depth, height, output_file);
-    assert!(true);
+    wfc::run(sample_dir, width, depth, height, output_file, seed, &policy).unwrap();
+    assert!(std::path::Path::new(output_file).exists());
 }
 
 #[test]
@@ -37,9 +44,11 @@ fn test_chaos_fortress() {
     let width = 20;
     let depth = 20;
     let height = 5;
+    let seed = 0;
+    let policy = wfc::RetryPolicy::default();
     let output_file = "tests/output/chaos_fortress.txt";
-    wfc::run(sample_dir, width, depth, height, output_file);
-    assert!(true);
+    wfc::run(sample_dir, width, depth, height, output_file, seed, &policy).unwrap();
+    assert!(std::path::Path::new(output_file).exists());
 }
 
 #[test]
@@ -48,9 +57,30 @@ fn test_stairs() {
     let width = 16;
     let depth = 16;
     let height = 8;
+    let seed = 0;
+    let policy = wfc::RetryPolicy::default();
     let output_file = "tests/output/stairs.txt";
-    wfc::run(sample_dir, width, depth, height, output_file);
-    assert!(true);
+    wfc::run(sample_dir, width, depth, height, output_file, seed, &policy).unwrap();
+    assert!(std::path::Path::new(output_file).exists());
+}
+
+#[test]
+fn test_tileset() {
+    let sample_dir = "tests/samples/tileset";
+    let width = 16;
+    let depth = 16;
+    let height = 8;
+    let seed = 0;
+    let policy = wfc::RetryPolicy::default();
+    let output_file = "tests/output/tileset.txt";
+    wfc::run(sample_dir, width, depth, height, output_file, seed, &policy).unwrap();
+    assert!(std::path::Path::new(output_file).exists());
+
+    let tileset = wfc::Model::new(sample_dir, 8, 8, 4, output_file).solve(seed);
+    let stairs = wfc::Model::new("tests/samples/stairs", 8, 8, 4, output_file).solve(seed);
+    let tileset_tiles = Vec::from_iter(tileset.unwrap().cells().iter().map(|cell| cell.tile));
+    let stairs_tiles = Vec::from_iter(stairs.unwrap().cells().iter().map(|cell| cell.tile));
+    assert_eq!(tileset_tiles, stairs_tiles);
 }
 
 #[test]
@@ -59,7 +89,445 @@ fn test_abstract() {
     let width = 16;
     let depth = 16;
     let height = 16;
+    let seed = 0;
+    let policy = wfc::RetryPolicy::default();
     let output_file = "tests/output/abstract.txt";
-    wfc::run(sample_dir, width, depth, height, output_file);
-    assert!(true);
+    wfc::run(sample_dir, width, depth, height, output_file, seed, &policy).unwrap();
+    assert!(std::path::Path::new(output_file).exists());
+}
+
+#[test]
+fn test_seed_reproducible() {
+    let sample_dir = "tests/samples/stairs";
+    let width = 8;
+    let depth = 8;
+    let height = 4;
+    let seed = 42;
+    let policy = wfc::RetryPolicy::default();
+    let first_output_file = "tests/output/seed_reproducible_0.txt";
+    let second_output_file = "tests/output/seed_reproducible_1.txt";
+    let first_seed = wfc::run(sample_dir, width, depth, height, first_output_file, seed, &policy).unwrap();
+    let second_seed = wfc::run(sample_dir, width, depth, height, second_output_file, first_seed, &policy).unwrap();
+    assert_eq!(first_seed, second_seed);
+    let first_output = std::fs::read_to_string(first_output_file).unwrap();
+    let second_output = std::fs::read_to_string(second_output_file).unwrap();
+    assert_eq!(first_output, second_output);
+}
+
+#[test]
+fn test_solve() {
+    let sample_dir = "tests/samples/stairs";
+    let width = 6;
+    let depth = 5;
+    let height = 4;
+    let output_file = "tests/output/solve.txt";
+    let model = wfc::Model::new(sample_dir, width, depth, height, output_file);
+    let grid = (0..100).find_map(|seed| model.solve(seed).ok()).unwrap();
+    assert_eq!(grid.width(), width);
+    assert_eq!(grid.depth(), depth);
+    assert_eq!(grid.height(), height);
+    assert_eq!(grid.cells().len(), width * depth * height);
+    assert_eq!(grid.get(1, 2, 3), &grid.cells()[1 + 2 * width + 3 * width * depth]);
+    assert!(!std::path::Path::new(output_file).exists());
+}
+
+#[test]
+fn test_missing_sample_dir() {
+    let model = wfc::Model::new("tests/samples/missing", 4, 4, 4, "tests/output/missing.txt");
+    assert!(matches!(model.solve(0), Err(wfc::Error::Io(_))));
+}
+
+#[test]
+fn test_retry_gives_up() {
+    let sample_dir = "tests/samples/odd_cycle";
+    let output_file = "tests/output/odd_cycle.txt";
+    // The two tiles alternate along x, which cannot wrap around an odd width.
+    let mut model = wfc::Model::new(sample_dir, 3, 1, 1, output_file);
+    model.set_boundary(wfc::Face::Right, wfc::Boundary::Periodic);
+    let policy = wfc::RetryPolicy::new(Some(3), None, wfc::SeedDerivation::Sequential);
+    match model.solve_with_retries(0, &policy) {
+        Err(wfc::Error::GaveUp(attempts)) => {
+            assert_eq!(attempts.len(), 3);
+            let seeds: Vec<u64> = attempts.iter().map(|(seed, _)| *seed).collect();
+            assert_eq!(seeds, vec![0, 1, 2]);
+            let face_counts = attempts.face_counts();
+            assert_eq!(face_counts.iter().map(|(_, count)| count).sum::<usize>(), 3);
+        }
+        _ => panic!("expected wfc::Error::GaveUp"),
+    }
+}
+
+#[test]
+fn test_backtracking() {
+    let sample_dir = "tests/samples/chaos_fortress";
+    let width = 16;
+    let depth = 16;
+    let height = 5;
+    let output_file = "tests/output/chaos_fortress_backtracking.txt";
+    let mut model = wfc::Model::new(sample_dir, width, depth, height, output_file);
+    model.set_backtrack_depth(64);
+    let policy = wfc::RetryPolicy::new(Some(1), None, wfc::SeedDerivation::Chained);
+    for seed in 0..4 {
+        let grid = model.solve_with_retries(seed, &policy).unwrap();
+        assert_eq!(grid.seed(), seed);
+    }
+}
+
+#[test]
+fn test_retry_unsatisfiable() {
+    let sample_dir = "tests/samples/unsatisfiable";
+    let output_file = "tests/output/unsatisfiable.txt";
+    let result = wfc::run(sample_dir, 2, 2, 2, output_file, 0, &wfc::RetryPolicy::default());
+    assert!(matches!(result, Err(wfc::Error::Unsatisfiable(_))));
+}
+
+#[test]
+fn test_backtracking_unsatisfiable() {
+    let sample_dir = "tests/samples/unsatisfiable";
+    let output_file = "tests/output/unsatisfiable_backtracking.txt";
+    let mut model = wfc::Model::new(sample_dir, 2, 2, 2, output_file);
+    model.set_backtrack_depth(8);
+    assert!(matches!(model.solve(0), Err(wfc::Error::Unsatisfiable(_))));
+}
+
+#[test]
+fn test_contradiction_names() {
+    let sample_dir = "tests/samples/unsatisfiable";
+    let output_file = "tests/output/unsatisfiable_names.txt";
+    let model = wfc::Model::new(sample_dir, 2, 2, 2, output_file);
+    match model.solve(0) {
+        Err(wfc::Error::Unsatisfiable(c)) => {
+            assert_eq!(c.names.len(), c.tiles.len());
+            assert!(c.names.iter().all(|name| name.starts_with("unsatisfiable-")));
+        }
+        _ => panic!("expected wfc::Error::Unsatisfiable"),
+    }
+}
+
+#[test]
+fn test_slab_tiles() {
+    let sample_dir = "tests/samples/slab";
+    let output_file = "tests/output/slab.vox";
+    let mut model = wfc::Model::new(sample_dir, 4, 3, 2, output_file);
+    model.set_output_format(wfc::OutputFormat::Vox);
+    model.wfc(0, &wfc::RetryPolicy::default()).unwrap();
+    let vox = wfc::Vox::open(output_file).unwrap();
+    assert_eq!(vox.sizes(), vec![[64, 48, 16]]);
+}
+
+#[test]
+fn test_validate() {
+    let model = wfc::Model::new("tests/samples/concrete", 1, 1, 1, "tests/output/validate.txt");
+    let report = model.validate().unwrap();
+    assert!(report.asymmetric_adjacencies.is_empty());
+    let model = wfc::Model::new("tests/samples/asymmetric", 1, 1, 1, "tests/output/validate.txt");
+    let report = model.validate().unwrap();
+    assert!(!report.is_empty());
+    assert!(report.to_string().contains("asymmetric-2-island"));
+}
+
+#[test]
+fn test_pins() {
+    let sample_dir = "tests/samples/tileset";
+    let output_file = "tests/output/pins.txt";
+    let mut model = wfc::Model::new(sample_dir, 6, 6, 2, output_file);
+    model.pin(2, 3, 0, wfc::Pin::Tile("tileset-1-path".to_string()));
+    model.pin(0, 0, 0, wfc::Pin::Allow(vec!["ground".to_string()]));
+    model.pin(5, 5, 1, wfc::Pin::Forbid(vec!["tileset-0-empty".to_string()]));
+    let grid = model.solve(0).unwrap();
+    assert_eq!(grid.get(2, 3, 0).name, "tileset-1-path");
+    assert_eq!(grid.get(0, 0, 0).name, "tileset-1-path");
+    assert!(grid.get(5, 5, 1).name.starts_with("tileset-3-stairs_1"));
+}
+
+#[test]
+fn test_invalid_pins() {
+    let sample_dir = "tests/samples/tileset";
+    let output_file = "tests/output/invalid_pins.txt";
+    let mut model = wfc::Model::new(sample_dir, 4, 4, 2, output_file);
+    model.pin(1, 1, 0, wfc::Pin::Tile("tileset-1-path".to_string()));
+    model.pin(1, 1, 0, wfc::Pin::Tile("tileset-0-empty".to_string()));
+    assert!(matches!(model.solve(0), Err(wfc::Error::ConflictingPins { x: 1, y: 1, z: 0 })));
+
+    // No tile has the down connector of the stairs' up faces, which propagation finds before solving.
+    let mut model = wfc::Model::new(sample_dir, 4, 4, 2, output_file);
+    model.pin(2, 2, 0, wfc::Pin::Allow(vec!["stairs".to_string()]));
+    assert!(matches!(model.solve(0), Err(wfc::Error::ConflictingPins { .. })));
+
+    let mut model = wfc::Model::new(sample_dir, 4, 4, 2, output_file);
+    model.pin(1, 1, 0, wfc::Pin::Allow(vec!["bridges".to_string()]));
+    assert!(matches!(model.solve(0), Err(wfc::Error::InvalidPin(_))));
+
+    let mut model = wfc::Model::new(sample_dir, 4, 4, 2, output_file);
+    model.pin(4, 1, 0, wfc::Pin::Tile("tileset-1-path".to_string()));
+    assert!(matches!(model.solve(0), Err(wfc::Error::InvalidPin(_))));
+}
+
+#[test]
+fn test_boundaries() {
+    let sample_dir = "tests/samples/tileset";
+    let output_file = "tests/output/boundaries.txt";
+    let mut model = wfc::Model::new(sample_dir, 6, 6, 2, output_file);
+    model.set_boundary(wfc::Face::Down, wfc::Boundary::Connector(99));
+    let grid = model.solve(0).unwrap();
+    for y in 0..6 {
+        for x in 0..6 {
+            assert_eq!(grid.get(x, y, 0).name, "tileset-1-path");
+        }
+    }
+
+    model.set_boundary(wfc::Face::Down, wfc::Boundary::Open);
+    model.set_boundary(wfc::Face::Left, wfc::Boundary::Connector(0));
+    let grid = model.solve(0).unwrap();
+    for z in 0..2 {
+        for y in 0..6 {
+            assert_ne!(grid.get(0, y, z).name, "tileset-1-path");
+        }
+    }
+
+    model.set_boundary(wfc::Face::Up, wfc::Boundary::Connector(5));
+    assert!(matches!(model.solve(0), Err(wfc::Error::UnsatisfiableBoundary(wfc::Face::Up))));
+}
+
+#[test]
+fn test_periodic() {
+    let sample_dir = "tests/samples/tileset";
+    let output_file = "tests/output/periodic.txt";
+    // No tile has the path's down connector on its up face.
+    let mut model = wfc::Model::new(sample_dir, 1, 1, 2, output_file);
+    model.pin(0, 0, 0, wfc::Pin::Tile("tileset-1-path".to_string()));
+    model.solve(0).unwrap();
+    model.set_boundary(wfc::Face::Down, wfc::Boundary::Periodic);
+    assert!(matches!(model.solve(0), Err(wfc::Error::ConflictingPins { .. })));
+    model.set_boundary(wfc::Face::Up, wfc::Boundary::Open);
+    model.solve(0).unwrap();
+
+    // Only the empty tile fits against itself on every face.
+    let mut model = wfc::Model::new(sample_dir, 1, 1, 1, output_file);
+    for face in [wfc::Face::Left, wfc::Face::Front, wfc::Face::Down] {
+        model.set_boundary(face, wfc::Boundary::Periodic);
+    }
+    let grid = model.solve(0).unwrap();
+    assert_eq!(grid.get(0, 0, 0).name, "tileset-0-empty");
+
+    let mut model = wfc::Model::new(sample_dir, 8, 8, 2, output_file);
+    model.set_boundary(wfc::Face::Right, wfc::Boundary::Periodic);
+    model.set_boundary(wfc::Face::Back, wfc::Boundary::Periodic);
+    model.wfc(0, &wfc::RetryPolicy::default()).unwrap();
+    let output = std::fs::read_to_string(output_file).unwrap();
+    assert!(output.lines().any(|line| line == "// periodic x y"));
+
+    let output_file = "tests/output/periodic.vox";
+    let mut model = wfc::Model::new(sample_dir, 4, 4, 2, output_file);
+    model.set_boundary(wfc::Face::Right, wfc::Boundary::Periodic);
+    model.set_boundary(wfc::Face::Back, wfc::Boundary::Periodic);
+    model.set_output_format(wfc::OutputFormat::Vox);
+    model.wfc(0, &wfc::RetryPolicy::default()).unwrap();
+    let vox = wfc::Vox::open(output_file).unwrap();
+    assert_eq!(vox.root_attribute("_periodic"), Some("x y"));
+}
+
+#[test]
+fn test_wrong_tile_size() {
+    let sample_dir = "tests/samples/wrong_size";
+    let output_file = "tests/output/wrong_size.txt";
+    let model = wfc::Model::new(sample_dir, 2, 2, 2, output_file);
+    assert!(matches!(model.solve(0), Err(wfc::Error::InvalidConfig(_))));
+}
+
+#[test]
+fn test_no_tiles() {
+    let sample_dir = "tests/samples/empty";
+    let output_file = "tests/output/empty.txt";
+    let model = wfc::Model::new(sample_dir, 1, 1, 1, output_file);
+    assert!(matches!(model.solve(0), Err(wfc::Error::InvalidConfig(_))));
+}
+
+#[test]
+fn test_invalid_tile_name() {
+    let sample_dir = "tests/samples/invalid_name";
+    let output_file = "tests/output/invalid_name.txt";
+    let model = wfc::Model::new(sample_dir, 2, 2, 2, output_file);
+    assert!(matches!(model.solve(0), Err(wfc::Error::InvalidTileName(_))));
+}
+
+#[test]
+fn test_weights() {
+    let sample_dir = "tests/samples/weights";
+    let output_file = "tests/output/weights.txt";
+    let model = wfc::Model::new(sample_dir, 4, 4, 1, output_file);
+    let grid = model.solve(0).unwrap();
+    for cell in grid.cells() {
+        assert_eq!(cell.name, "weights-0-empty");
+    }
+}
+
+#[test]
+fn test_heuristics() {
+    let sample_dir = "tests/samples/stairs";
+    let output_file = "tests/output/heuristics.txt";
+    let mut model = wfc::Model::new(sample_dir, 8, 8, 4, output_file);
+    model.set_backtrack_depth(32);
+    let heuristics = [
+        wfc::Heuristic::MinCount,
+        wfc::Heuristic::Shannon,
+        wfc::Heuristic::Scanline,
+        wfc::Heuristic::Random,
+    ];
+    for heuristic in heuristics {
+        model.set_heuristic(heuristic);
+        let first = model.solve(7).unwrap();
+        let second = model.solve(7).unwrap();
+        assert_eq!(first, second);
+    }
+}
+
+#[derive(Default)]
+struct CountingObserver {
+    observations: std::cell::Cell<usize>,
+    progress: std::cell::Cell<usize>,
+    contradictions: std::cell::Cell<usize>,
+}
+
+impl wfc::Observer for CountingObserver {
+    fn observe(&self, _wave: usize, _tile: usize) {
+        self.observations.set(self.observations.get() + 1);
+    }
+
+    fn progress(&self, percent: usize) {
+        assert!(percent > self.progress.get());
+        self.progress.set(percent);
+    }
+
+    fn contradiction(&self, contradiction: &wfc::Contradiction) {
+        assert_eq!(contradiction.names.len(), contradiction.tiles.len());
+        self.contradictions.set(self.contradictions.get() + 1);
+    }
+}
+
+#[test]
+fn test_observer() {
+    let sample_dir = "tests/samples/stairs";
+    let output_file = "tests/output/observer.txt";
+    let mut model = wfc::Model::new(sample_dir, 8, 8, 4, output_file);
+    model.set_backtrack_depth(32);
+    let observer = std::rc::Rc::new(CountingObserver::default());
+    model.set_observer(Box::new(observer.clone()));
+    model.solve(7).unwrap();
+    assert!(observer.observations.get() > 0);
+    assert_eq!(observer.progress.get(), 100);
+
+    let sample_dir = "tests/samples/unsatisfiable";
+    let mut model = wfc::Model::new(sample_dir, 2, 2, 2, output_file);
+    let observer = std::rc::Rc::new(CountingObserver::default());
+    model.set_observer(Box::new(observer.clone()));
+    match model.debug(0) {
+        Err(wfc::Error::Unsatisfiable(c)) => assert_eq!(c.names.len(), c.tiles.len()),
+        _ => panic!("expected wfc::Error::Unsatisfiable"),
+    }
+    assert_eq!(observer.contradictions.get(), 1);
+}
+
+#[test]
+fn test_vox_output() {
+    let sample_dir = "tests/samples/stairs";
+    let output_file = "tests/output/stairs.vox";
+    let mut model = wfc::Model::new(sample_dir, 20, 20, 2, output_file);
+    model.set_backtrack_depth(32);
+    model.set_output_format(wfc::OutputFormat::Vox);
+    let grid = model.wfc(0, &wfc::RetryPolicy::default()).unwrap();
+    let bytes = std::fs::read(output_file).unwrap();
+    assert_eq!(&bytes[..4], b"VOX ");
+    let vox = wfc::Vox::open(output_file).unwrap();
+    let mut sizes = vox.sizes();
+    sizes.sort();
+    assert_eq!(sizes, vec![[64, 64, 32], [64, 256, 32], [256, 64, 32], [256, 256, 32]]);
+    let tile_voxel_count = |cell: &wfc::Cell| {
+        let config_name = cell.name.split('@').next().unwrap();
+        let tile = wfc::Vox::open(format!("{}/{}.vox", sample_dir, config_name)).unwrap();
+        tile.color_counts().iter().sum::<usize>()
+    };
+    let voxel_count: usize = grid.cells().iter().map(tile_voxel_count).sum();
+    assert_eq!(vox.color_counts().iter().sum::<usize>(), voxel_count);
+    assert_eq!(vox.root_attribute("_seed"), Some(grid.seed().to_string().as_str()));
+    assert_eq!(vox.root_attribute("_periodic"), None);
+
+    let model = wfc::Model::new(sample_dir, 0, 2, 2, output_file);
+    let grid = model.solve(0).unwrap();
+    assert!(matches!(model.render_vox(&grid, [16, 16, 16], &[]), Err(wfc::Error::EmptyGrid)));
+}
+
+#[test]
+fn test_sample_dir_unchanged() {
+    let sample_dir = "tests/samples/stairs";
+    let output_file = "tests/output/unchanged/stairs.txt";
+    let list = || {
+        let entries = std::fs::read_dir(sample_dir).unwrap();
+        let mut names = Vec::from_iter(entries.map(|entry| entry.unwrap().file_name()));
+        names.sort();
+        names
+    };
+    let before = list();
+    let policy = wfc::RetryPolicy::default();
+    wfc::run(sample_dir, 8, 8, 4, output_file, 0, &policy).unwrap();
+    assert_eq!(list(), before);
+    let mv_import = std::fs::read_to_string(output_file).unwrap();
+    assert!(mv_import.contains("unchanged/tiles/"));
+}
+
+#[test]
+fn test_cube_symmetry() {
+    let sample_dir = "tests/samples/cube";
+    let output_file = "tests/output/cube.txt";
+    let mut model = wfc::Model::new(sample_dir, 3, 3, 3, output_file);
+    model.set_backtrack_depth(64);
+    let grid = model.solve(0).unwrap();
+    let tipped = grid.cells().iter().any(|cell| cell.orientation.apply([0, 0, 1]) != [0, 0, 1]);
+    assert!(tipped);
+
+    let sample_dir = "tests/samples/cube_asymmetric";
+    let mut model = wfc::Model::new(sample_dir, 3, 3, 3, output_file);
+    model.set_backtrack_depth(64);
+    let grid = model.solve(0).unwrap();
+    let tipped = grid.cells().iter().any(|cell| cell.orientation.apply([0, 0, 1]) != [0, 0, 1]);
+    assert!(tipped);
+    let report = model.validate().unwrap();
+    assert!(report.dead_faces.is_empty());
+    assert!(report.asymmetric_adjacencies.is_empty());
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn test_cli_exit_codes() {
+    let wfc = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_wfc")).args(args).output().unwrap();
+        output.status.code().unwrap()
+    };
+    let size = ["--width", "4", "--depth", "4", "--height", "2", "--seed", "0"];
+    let generate = [&["generate", "tests/samples/stairs", "tests/output/cli.txt"][..], &size].concat();
+    assert_eq!(wfc(&generate), 0);
+    assert!(std::path::Path::new("tests/output/cli.txt").exists());
+    let missing = [&["generate", "tests/samples/missing", "tests/output/cli.txt"][..], &size].concat();
+    assert_eq!(wfc(&missing), 1);
+    assert_eq!(wfc(&["generate", "tests/samples/stairs", "tests/output/cli.txt"]), 2);
+    for invalid in [["--time-budget", "-1"], ["--time-budget", "1e30"]] {
+        assert_eq!(wfc(&[&generate[..], &invalid].concat()), 2);
+    }
+    let empty = ["generate", "tests/samples/stairs", "tests/output/cli.txt", "--width", "0", "--depth", "4", "--height", "2"];
+    assert_eq!(wfc(&empty), 2);
+    assert_eq!(wfc(&["validate", "tests/samples/invalid_name"]), 3);
+    let no_tiles = ["generate", "tests/samples/empty", "tests/output/cli.txt", "--width", "1", "--depth", "1", "--height", "1"];
+    assert_eq!(wfc(&no_tiles), 3);
+    let unsatisfiable = ["generate", "tests/samples/unsatisfiable", "tests/output/cli.txt"];
+    assert_eq!(wfc(&[&unsatisfiable[..], &size].concat()), 4);
+    let odd_cycle = ["generate", "tests/samples/odd_cycle", "tests/output/cli.txt", "--max-attempts", "1"];
+    let odd_size = ["--boundary", "left=periodic", "--width", "3", "--depth", "1", "--height", "1"];
+    assert_eq!(wfc(&[&odd_cycle[..], &odd_size].concat()), 5);
+    assert_eq!(wfc(&["inspect", "tests/samples/stairs/stairs.vox"]), 0);
+    assert_eq!(wfc(&["validate", "tests/samples/asymmetric"]), 0);
+    assert_eq!(wfc(&["validate", "--strict", "tests/samples/asymmetric"]), 3);
+    let boundary = ["generate", "tests/samples/tileset", "tests/output/cli.txt", "--boundary", "down=5"];
+    assert_eq!(wfc(&[&boundary[..], &size].concat()), 4);
+    assert_eq!(wfc(&[&boundary[..3], &["--boundary", "below=99"], &size].concat()), 2);
+    assert_eq!(wfc(&[&boundary[..3], &["--boundary", "left=periodic"], &size].concat()), 0);
 }